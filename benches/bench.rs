@@ -17,7 +17,10 @@ mod tests {
     use test::Bencher;
     use rand::{XorShiftRng, StdRng, IsaacRng, Isaac64Rng, Rng, ChaChaRng};
     use rand::{OsRng, weak_rng, thread_rng};
-    use differential_evolution::{Population, Settings};
+    use differential_evolution::{Adaptation, BoundaryHandling, ConstraintHandling,
+                                 Initialization, Population, Settings, Updating};
+    use differential_evolution::mutation::Rand1;
+    use differential_evolution::crossover::Binomial;
 
 
     fn setup<F: Fn(&[f32]) -> C, R: Rng, C: PartialOrd + Clone>(dim: usize,
@@ -26,13 +29,31 @@ mod tests {
                                                  -> Population<F, R, C> {
         let s = Settings {
             min_max_pos: vec![(-100.0, 100.0); dim],
+            bounds: None,
+            boundary_handling: BoundaryHandling::Midpoint,
             cr_min_max: (0.0, 1.0),
             cr_change_probability: 0.1,
             f_min_max: (0.1, 1.0),
             f_change_probability: 0.1,
+            adaptation: Adaptation::Jde,
+            archive_size: 0,
+            improvement: None,
             pop_size: 100,
-            rng: rng,
+            pop_reduction: None,
+            mutation: Box::new(Rand1),
+            crossover: Box::new(Binomial),
+            initialization: Initialization::Uniform,
+            initial_candidates: Vec::new(),
+            rng,
             cost_function: cost_fn,
+            max_retries: 0,
+            constraints: None,
+            constraint_handling: ConstraintHandling::Feasibility { tolerance: 1e-4 },
+            observer: None,
+            memetic: None,
+            noise: None,
+            updating: Updating::Deferred,
+            search_space: None,
         };
         Population::new(s)
     }
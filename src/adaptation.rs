@@ -0,0 +1,208 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Adaptation of the control parameters `cr` and `f`.
+
+use std::f64::consts::PI;
+use rand::Rng;
+use rand::distributions::{IndependentSample, Range};
+use rand::distributions::normal::Normal;
+
+/// Selects how the control parameters `cr` and `f` of each individual
+/// are adapted while the DE runs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Adaptation {
+    /// jDE, as described in "Self-Adapting Control Parameters in
+    /// Differential Evolution: A Comparative Study on Numerical Benchmark
+    /// Problems". Each individual inherits its parent's parameters, which are
+    /// resampled uniformly with a given probability. This uses
+    /// `cr_min_max`, `cr_change_probability`, `f_min_max` and
+    /// `f_change_probability` of the settings.
+    Jde,
+
+    /// JADE, as described in "JADE: Adaptive Differential Evolution With
+    /// Optional External Archive". `cr` is sampled from a normal and `f` from
+    /// a Cauchy distribution around means that move towards the parameters
+    /// of the individuals that have improved. `c` is the learning rate of
+    /// the means, 0.1 is a reasonable choice.
+    Jade {
+        /// Learning rate of the means, between 0 and 1.
+        c: f32,
+    },
+
+    /// SHADE, as described in "Success-History Based Parameter Adaptation
+    /// for Differential Evolution". Like JADE, but instead of a single mean
+    /// it keeps a history of the weighted means of successful parameters
+    /// for the last `memory_size` generations, and samples around a random
+    /// entry. L-SHADE uses a memory size of 6.
+    Shade {
+        /// Number of generations in the history.
+        memory_size: usize,
+    },
+
+    /// No adaptation, like scipy's `differential_evolution`. All trials
+    /// use the crossover probability `cr`, and share an `f` that is drawn
+    /// uniformly from `f_min_max` for each generation (dithering). Use
+    /// `(f, f)` for a constant `f`.
+    Constant {
+        /// Crossover probability of all trials.
+        cr: f32,
+
+        /// Range `(min, max)` of `f`, with `min <= max`.
+        f_min_max: (f32, f32),
+    },
+}
+
+// Marks a memory entry of `cr` after all successful individuals had cr = 0.
+// From then on, that entry always produces cr = 0.
+const TERMINAL_CR: f32 = -1.0;
+
+// Attempts to sample a positive `f` before it is truncated to MIN_F.
+const MAX_F_ATTEMPTS: usize = 100;
+const MIN_F: f32 = 0.01;
+
+/// State of the success based adaptation: the memory of the means, and
+/// the parameters of all individuals that have improved in this
+/// generation.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SuccessMemory {
+    // JADE uses a single entry, SHADE one per generation of the history.
+    m_cr: Vec<f32>,
+    m_f: Vec<f32>,
+    next_entry: usize,
+
+    successful_cr: Vec<f32>,
+    successful_f: Vec<f32>,
+    weights: Vec<f64>,
+}
+
+impl SuccessMemory {
+    pub fn new(adaptation: &Adaptation) -> SuccessMemory {
+        let size = match *adaptation {
+            Adaptation::Shade { memory_size } => memory_size,
+            _ => 1,
+        };
+        assert!(size > 0, "memory_size needs to be at least 1");
+        SuccessMemory {
+            m_cr: vec![0.5; size],
+            m_f: vec![0.5; size],
+            next_entry: 0,
+            successful_cr: Vec::new(),
+            successful_f: Vec::new(),
+            weights: Vec::new(),
+        }
+    }
+
+    /// Samples `(cr, f)` for a new individual.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> (f32, f32) {
+        let r = if self.m_cr.len() == 1 {
+            0
+        } else {
+            Range::new(0, self.m_cr.len()).ind_sample(rng)
+        };
+
+        let cr = if self.m_cr[r] == TERMINAL_CR {
+            0.0
+        } else {
+            let cr = Normal::new(self.m_cr[r] as f64, 0.1).ind_sample(rng);
+            (cr as f32).clamp(0.0, 1.0)
+        };
+        (cr, sample_f(rng, self.m_f[r]))
+    }
+
+    /// Remembers the parameters of an individual that has improved. The
+    /// `weight` is only used by SHADE.
+    pub fn record(&mut self, cr: f32, f: f32, weight: f64) {
+        self.successful_cr.push(cr);
+        self.successful_f.push(f);
+        self.weights.push(weight);
+    }
+
+    /// Updates the memory with the recorded parameters, and starts a new
+    /// generation.
+    pub fn update(&mut self, adaptation: &Adaptation) {
+        if !self.successful_cr.is_empty() {
+            match *adaptation {
+                Adaptation::Jde |
+                Adaptation::Constant { .. } => {}
+                Adaptation::Jade { c } => {
+                    // arithmetic mean for cr, and Lehmer mean for f, which
+                    // favours larger f to counteract premature convergence.
+                    let mean_cr = self.successful_cr.iter().fold(0.0, |sum, x| sum + x) /
+                                  self.successful_cr.len() as f32;
+                    let mean_f = lehmer_mean(&self.successful_f, None, self.m_f[0]);
+                    self.m_cr[0] = (1.0 - c) * self.m_cr[0] + c * mean_cr;
+                    self.m_f[0] = (1.0 - c) * self.m_f[0] + c * mean_f;
+                }
+                Adaptation::Shade { .. } => {
+                    // improvements that are not finite, e.g. from an infinite
+                    // parent cost, count as much as the largest finite one.
+                    let max_weight = self.weights
+                        .iter()
+                        .cloned()
+                        .filter(|w| w.is_finite())
+                        .fold(0.0, f64::max);
+                    let cap = if max_weight > 0.0 { max_weight } else { 1.0 };
+                    for w in &mut self.weights {
+                        if !w.is_finite() {
+                            *w = cap;
+                        }
+                    }
+
+                    let k = self.next_entry;
+                    let max_cr = self.successful_cr.iter().fold(0.0f32, |m, &x| m.max(x));
+                    if self.m_cr[k] == TERMINAL_CR || max_cr == 0.0 {
+                        self.m_cr[k] = TERMINAL_CR;
+                    } else {
+                        self.m_cr[k] =
+                            lehmer_mean(&self.successful_cr, Some(&self.weights), self.m_cr[k]);
+                    }
+                    self.m_f[k] = lehmer_mean(&self.successful_f, Some(&self.weights), self.m_f[k]);
+                    self.next_entry = (k + 1) % self.m_cr.len();
+                }
+            }
+        }
+        self.successful_cr.clear();
+        self.successful_f.clear();
+        self.weights.clear();
+    }
+}
+
+/// Weighted sum of squares divided by the weighted sum. Without weights,
+/// all values are weighted equally. Returns `old` when the mean is not
+/// defined.
+pub fn lehmer_mean(values: &[f32], weights: Option<&[f64]>, old: f32) -> f32 {
+    let mut sum = 0.0;
+    let mut sum_sq = 0.0;
+    for (i, &x) in values.iter().enumerate() {
+        let w = weights.map_or(1.0, |w| w[i]);
+        sum += w * x as f64;
+        sum_sq += w * x as f64 * x as f64;
+    }
+    let mean = (sum_sq / sum) as f32;
+    if sum <= 0.0 || !mean.is_finite() {
+        old
+    } else {
+        mean
+    }
+}
+
+/// Samples `f` from a Cauchy distribution with scale 0.1 around `mu_f`.
+/// Regenerated when not positive, truncated to 1 when larger. When no
+/// positive value is found after many attempts, it is truncated to a small
+/// positive value instead.
+pub fn sample_f<R: Rng>(rng: &mut R, mu_f: f32) -> f32 {
+    for _ in 0..MAX_F_ATTEMPTS {
+        let f = mu_f as f64 + 0.1 * (PI * (rng.gen::<f64>() - 0.5)).tan();
+        if f > 0.0 {
+            return f.min(1.0) as f32;
+        }
+    }
+    MIN_F
+}
@@ -0,0 +1,82 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Repairs trial vectors that have left the hard bounds.
+
+use rand::Rng;
+use rand::distributions::{IndependentSample, Range};
+use float::Float;
+
+/// Selects how a dimension of a trial vector that lies outside of the
+/// hard bounds is brought back inside.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BoundaryHandling {
+    /// Sets the value to the violated bound. Simple, but many individuals
+    /// end up exactly on the bound.
+    Clamp,
+
+    /// Mirrors the value at the violated bound.
+    Reflect,
+
+    /// Wraps around, so leaving at the upper bound enters at the lower
+    /// bound. Best suited for periodic variables like angles.
+    Wrap,
+
+    /// Replaces the value with a uniformly random value within the bounds.
+    Random,
+
+    /// Uses the midpoint between the violated bound and the parent's value.
+    /// This is what JADE and SHADE use.
+    Midpoint,
+}
+
+impl BoundaryHandling {
+    /// Returns `x` if it is within `min_max`, otherwise the repaired value.
+    pub fn repair<R: Rng, T: Float>(&self, rng: &mut R, min_max: (T, T), parent: T, x: T) -> T {
+        let (min, max) = min_max;
+        if x >= min && x <= max {
+            return x;
+        }
+        if max <= min {
+            return min;
+        }
+
+        let width = (max - min).to_f64();
+        let repaired = match *self {
+            BoundaryHandling::Clamp => if x < min { min } else { max },
+            BoundaryHandling::Reflect => {
+                // fold back and forth, in case it is far away.
+                let y = (x - min).to_f64().rem_euclid(2.0 * width);
+                if y > width {
+                    min + T::from_f64(2.0 * width - y)
+                } else {
+                    min + T::from_f64(y)
+                }
+            }
+            BoundaryHandling::Wrap => min + T::from_f64((x - min).to_f64().rem_euclid(width)),
+            BoundaryHandling::Random => Range::new(min, max).ind_sample(rng),
+            BoundaryHandling::Midpoint => {
+                let two = T::from_f64(2.0);
+                if x < min {
+                    (min + parent) / two
+                } else {
+                    (max + parent) / two
+                }
+            }
+        };
+
+        // guard against rounding errors.
+        if repaired < min {
+            min
+        } else if repaired > max {
+            max
+        } else {
+            repaired
+        }
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Saving and restoring the state of a population.
+
+use rand::Rng;
+use rand::distributions::Range;
+use adaptation::SuccessMemory;
+use super::{Constraint, Float, Individual, Population, Settings};
+
+/// The complete state of a `Population`, without its settings. With the
+/// `serde` feature it can be serialized, to resume long runs after they
+/// have been interrupted.
+///
+/// The random number generator is part of the state, so a resumed run
+/// continues exactly like the uninterrupted run would have. This requires
+/// an RNG that can be cloned and serialized, like `XorShift128`:
+///
+/// ```
+/// use differential_evolution::{Population, Settings, XorShift128};
+///
+/// let cost = |pos: &[f32]| pos.iter().fold(0.0, |sum, x| sum + x * x);
+/// let settings = || Settings::default(vec![(-10.0, 10.0); 3], cost).with_rng(XorShift128::new());
+///
+/// let mut de = Population::new(settings());
+/// de.iter().nth(1000);
+/// let checkpoint = de.checkpoint();
+///
+/// // later, possibly in another process after deserializing the checkpoint.
+/// let mut de = Population::resume(settings(), checkpoint);
+/// de.iter().nth(1000);
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Checkpoint<C, T, R>
+    where C: PartialOrd + Clone
+{
+    curr: Vec<Individual<C, T>>,
+    best: Vec<Individual<C, T>>,
+    archive: Vec<Vec<T>>,
+    memory: SuccessMemory,
+    best_idx: Option<usize>,
+    best_cost_cache: Option<C>,
+    best_constraints_cache: Vec<Constraint<T>>,
+    num_cost_evaluations: usize,
+    num_failed_evaluations: usize,
+    run_start_evaluations: usize,
+    generation: usize,
+    pop_countdown: usize,
+    rng: R,
+}
+
+impl<F, R, C, T> Population<F, R, C, T>
+    where F: Fn(&[T]) -> C,
+          R: Rng + Clone,
+          C: PartialOrd + Clone,
+          T: Float
+{
+    /// Saves the state of the population, including the state of the
+    /// random number generator.
+    pub fn checkpoint(&self) -> Checkpoint<C, T, R> {
+        Checkpoint {
+            curr: self.curr.clone(),
+            best: self.best.clone(),
+            archive: self.archive.clone(),
+            memory: self.memory.clone(),
+            best_idx: self.best_idx,
+            best_cost_cache: self.best_cost_cache.clone(),
+            best_constraints_cache: self.best_constraints_cache.clone(),
+            num_cost_evaluations: self.num_cost_evaluations,
+            num_failed_evaluations: self.num_failed_evaluations,
+            run_start_evaluations: self.run_start_evaluations,
+            generation: self.generation,
+            pop_countdown: self.pop_countdown,
+            rng: self.settings.rng.clone(),
+        }
+    }
+
+    /// Restores a population from a checkpoint. The settings have to be the
+    /// same as the ones of the checkpointed population, except for the cost
+    /// function, the constraints and the observer. Their `rng` is replaced
+    /// by the one of the checkpoint.
+    pub fn resume(s: Settings<F, R, C, T>, checkpoint: Checkpoint<C, T, R>) -> Population<F, R, C, T> {
+        let mut pop = Population::new(s);
+        assert!(checkpoint.best.len() == checkpoint.curr.len() &&
+                checkpoint.curr.len() <= pop.curr.len() &&
+                checkpoint.curr.iter().all(|ind| ind.pos.len() == pop.settings.min_max_pos.len()),
+                "checkpoint does not fit to the settings");
+
+        pop.between_popsize = Range::new(0, checkpoint.curr.len());
+        pop.curr = checkpoint.curr;
+        pop.best = checkpoint.best;
+        pop.archive = checkpoint.archive;
+        pop.memory = checkpoint.memory;
+        pop.best_idx = checkpoint.best_idx;
+        pop.best_cost_cache = checkpoint.best_cost_cache;
+        pop.best_constraints_cache = checkpoint.best_constraints_cache;
+        pop.num_cost_evaluations = checkpoint.num_cost_evaluations;
+        pop.num_failed_evaluations = checkpoint.num_failed_evaluations;
+        pop.run_start_evaluations = checkpoint.run_start_evaluations;
+        pop.generation = checkpoint.generation;
+        pop.pop_countdown = checkpoint.pop_countdown;
+        pop.settings.rng = checkpoint.rng;
+        pop
+    }
+}
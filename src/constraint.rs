@@ -0,0 +1,88 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Constraints and how they are handled when comparing individuals.
+
+use float::Float;
+
+/// Value of a single constraint at a position, as returned by
+/// `Settings::constraints`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Constraint<T = f32> {
+    /// The value `g(x)` of an inequality constraint `g(x) <= 0`.
+    Inequality(T),
+
+    /// The value `h(x)` of an equality constraint `h(x) = 0`.
+    Equality(T),
+}
+
+impl<T: Float> Constraint<T> {
+    /// By how much the constraint is violated. Equality constraints are
+    /// satisfied when `|h(x)| <= tolerance`.
+    pub fn violation(&self, tolerance: f32) -> f64 {
+        match *self {
+            Constraint::Inequality(g) => g.to_f64().max(0.0),
+            Constraint::Equality(h) => (h.to_f64().abs() - tolerance as f64).max(0.0),
+        }
+    }
+}
+
+/// Returns the values of all constraints at the given position.
+pub type ConstraintFunction<T = f32> = Box<dyn Fn(&[T]) -> Vec<Constraint<T>> + Send>;
+
+/// Selects how individuals with constraint violations are compared. Both
+/// use Deb's feasibility rules: a feasible individual is always better
+/// than an infeasible one, two feasible individuals are compared by cost,
+/// and of two infeasible individuals the one with the lower total
+/// violation is better.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConstraintHandling {
+    /// Equality constraints are satisfied within a fixed tolerance.
+    Feasibility {
+        /// Tolerance of the equality constraints, e.g. 1e-4.
+        tolerance: f32,
+    },
+
+    /// Epsilon constrained method: the tolerance of the equality
+    /// constraints starts large, so that the DE can first find the region
+    /// around the feasible set, and then decays exponentially from
+    /// `initial_tolerance` to `final_tolerance` over `generations`
+    /// generations.
+    Epsilon {
+        /// Tolerance in the first generation.
+        initial_tolerance: f32,
+
+        /// Tolerance after `generations` generations, has to be positive.
+        final_tolerance: f32,
+
+        /// Number of generations until the final tolerance is reached.
+        generations: usize,
+    },
+}
+
+impl ConstraintHandling {
+    /// Tolerance of the equality constraints in the given generation.
+    pub fn tolerance(&self, generation: usize) -> f32 {
+        match *self {
+            ConstraintHandling::Feasibility { tolerance } => tolerance,
+            ConstraintHandling::Epsilon { initial_tolerance, final_tolerance, generations } => {
+                if generation >= generations {
+                    return final_tolerance;
+                }
+                let progress = generation as f32 / generations as f32;
+                initial_tolerance * (final_tolerance / initial_tolerance).powf(progress)
+            }
+        }
+    }
+}
+
+/// Sum of the violations of all constraints.
+pub fn total_violation<T: Float>(constraints: &[Constraint<T>], tolerance: f32) -> f64 {
+    constraints.iter().fold(0.0, |sum, c| sum + c.violation(tolerance))
+}
@@ -0,0 +1,77 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Crossover operators that combine the mutant with the target
+//! individual into the trial vector.
+
+use rand::Rng;
+use float::Float;
+use rand::distributions::{IndependentSample, Range};
+
+/// Combines the target and the mutant into the trial vector. Like
+/// `MutationStrategy`, it has to be `Send`.
+pub trait Crossover<T = f32>: Send {
+    /// `trial` contains the mutant, and is modified in place so that it
+    /// becomes the trial vector. `cr` is the crossover control parameter of
+    /// the individual.
+    fn crossover(&self, rng: &mut dyn Rng, cr: f32, target: &[T], trial: &mut [T]);
+}
+
+/// Binomial crossover, the `bin` in DE/rand/1/bin. Each dimension is
+/// taken from the mutant with probability `cr`, and at least one
+/// dimension always is.
+pub struct Binomial;
+
+impl<T: Float> Crossover<T> for Binomial {
+    fn crossover(&self, mut rng: &mut dyn Rng, cr: f32, target: &[T], trial: &mut [T]) {
+        let forced_mutation_dim = Range::new(0, trial.len()).ind_sample(&mut rng);
+        for d in 0..trial.len() {
+            if d != forced_mutation_dim && rng.next_f32() >= cr {
+                trial[d] = target[d];
+            }
+        }
+    }
+}
+
+/// Exponential crossover, the `exp` in DE/rand/1/exp. Takes a contiguous
+/// segment (wrapping around) of dimensions from the mutant, starting at a
+/// random dimension. The segment continues with probability `cr`. This
+/// works well when neighbouring variables are strongly linked.
+pub struct Exponential;
+
+impl<T: Float> Crossover<T> for Exponential {
+    fn crossover(&self, mut rng: &mut dyn Rng, cr: f32, target: &[T], trial: &mut [T]) {
+        let dim = trial.len();
+        let start = Range::new(0, dim).ind_sample(&mut rng);
+        let mut len = 1;
+        while len < dim && rng.next_f32() < cr {
+            len += 1;
+        }
+
+        // everything after the segment comes from the target.
+        for l in len..dim {
+            let d = (start + l) % dim;
+            trial[d] = target[d];
+        }
+    }
+}
+
+/// Arithmetic crossover: `target + cr * (mutant - target)`. This is
+/// rotation invariant, so it does not depend on the orientation of the
+/// coordinate system. Together with `mutation::Rand1` this is the
+/// DE/current-to-rand/1 variant.
+pub struct Arithmetic;
+
+impl<T: Float> Crossover<T> for Arithmetic {
+    fn crossover(&self, _rng: &mut dyn Rng, cr: f32, target: &[T], trial: &mut [T]) {
+        let cr = T::from_f64(cr as f64);
+        for d in 0..trial.len() {
+            trial[d] = target[d] + cr * (trial[d] - target[d]);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Costs of evaluations that can fail.
+
+use std::cmp::Ordering;
+
+/// Cost of a cost function that can fail, e.g. because a simulation did
+/// not converge. Cost functions that return a `Result<C, E>` or an
+/// `Option<C>` can be used by converting the result:
+///
+/// ```
+/// use differential_evolution::{self_adaptive_de, Fallible};
+///
+/// fn simulate(pos: &[f32]) -> Result<f32, String> {
+///     if pos[0] < 0.0 {
+///         return Err("diverged".to_string());
+///     }
+///     Ok(pos.iter().fold(0.0, |sum, x| sum + x * x))
+/// }
+///
+/// let mut de = self_adaptive_de(vec![(-10.0, 10.0); 3], |pos| Fallible::from(simulate(pos)));
+/// de.iter().nth(1000);
+/// ```
+///
+/// Like NaN, `Failed` is not comparable to anything. The population treats
+/// all costs that are not comparable to themselves, including NaN, as
+/// failed: they are worse than any other cost and any constraint
+/// violation, and they are counted in `num_failed_evaluations()` instead
+/// of `num_cost_evaluations()`. With `Settings::max_retries` failed
+/// evaluations are retried.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Fallible<C> {
+    /// The evaluation succeeded with this cost.
+    Cost(C),
+
+    /// The evaluation failed.
+    Failed,
+}
+
+impl<C: PartialEq> PartialEq for Fallible<C> {
+    fn eq(&self, other: &Fallible<C>) -> bool {
+        match (self, other) {
+            (Fallible::Cost(a), Fallible::Cost(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<C: PartialOrd> PartialOrd for Fallible<C> {
+    fn partial_cmp(&self, other: &Fallible<C>) -> Option<Ordering> {
+        match (self, other) {
+            (Fallible::Cost(a), Fallible::Cost(b)) => a.partial_cmp(b),
+            _ => None,
+        }
+    }
+}
+
+impl<C, E> From<Result<C, E>> for Fallible<C> {
+    fn from(result: Result<C, E>) -> Fallible<C> {
+        match result {
+            Ok(cost) => Fallible::Cost(cost),
+            Err(_) => Fallible::Failed,
+        }
+    }
+}
+
+impl<C> From<Option<C>> for Fallible<C> {
+    fn from(cost: Option<C>) -> Fallible<C> {
+        match cost {
+            Some(cost) => Fallible::Cost(cost),
+            None => Fallible::Failed,
+        }
+    }
+}
+
+/// True unless the cost is not comparable to itself, like NaN or
+/// `Fallible::Failed`.
+pub fn is_valid_cost<C: PartialOrd>(cost: &C) -> bool {
+    cost.partial_cmp(cost).is_some()
+}
+
+/// Evaluates the cost function, and retries at most `max_retries` times
+/// while the cost is not valid. Returns the last cost, and the number of
+/// failed evaluations before it.
+pub fn evaluate<F, C, T>(cost_function: &F, pos: &[T], max_retries: usize) -> (C, usize)
+    where F: Fn(&[T]) -> C,
+          C: PartialOrd
+{
+    let mut cost = cost_function(pos);
+    let mut num_retries = 0;
+    while num_retries < max_retries && !is_valid_cost(&cost) {
+        cost = cost_function(pos);
+        num_retries += 1;
+    }
+    (cost, num_retries)
+}
@@ -0,0 +1,50 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Floating point types that can be used for positions.
+
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Sub};
+use rand::Rand;
+use rand::distributions::range::SampleRange;
+
+/// A floating point type for the positions of the individuals. This is
+/// implemented for `f32` and `f64`. `f32` is faster, `f64` is necessary
+/// when a precision better than about 1e-7 is required.
+pub trait Float: Copy + Debug + PartialOrd + SampleRange + Rand + Send + Sync + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    /// Converts from `f64`, possibly losing precision.
+    fn from_f64(x: f64) -> Self;
+
+    /// Converts to `f64`.
+    fn to_f64(self) -> f64;
+}
+
+impl Float for f32 {
+    fn from_f64(x: f64) -> f32 {
+        x as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Float for f64 {
+    fn from_f64(x: f64) -> f64 {
+        x
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! How the initial positions are sampled from `min_max_pos`.
+
+use rand::Rng;
+use rand::distributions::{IndependentSample, Range};
+use float::Float;
+
+/// Selects how the positions of the initial population are distributed
+/// within `Settings::min_max_pos`. The space-filling methods avoid large
+/// unexplored regions, which matters most for small populations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Initialization {
+    /// Each coordinate is sampled independently and uniformly.
+    Uniform,
+
+    /// Latin hypercube sampling: in each dimension, every one of `pop_size`
+    /// equally wide slices contains exactly one individual.
+    LatinHypercube,
+
+    /// Halton sequence, randomly shifted so that each run starts
+    /// differently.
+    Halton,
+
+    /// Sobol sequence with a random digital shift. With more than 21
+    /// dimensions, the Halton sequence is used instead.
+    Sobol,
+
+    /// Opposition-based initialization: for each uniformly sampled
+    /// position `x` its opposite `min + max - x` is evaluated too, and the
+    /// better half of both is kept. This doubles the evaluations of the
+    /// first generation.
+    Opposition,
+}
+
+// Maximum number of dimensions of `Initialization::Sobol`.
+const SOBOL_MAX_DIM: usize = 1 + SOBOL_DIRECTIONS.len();
+
+impl Initialization {
+    /// Samples the initial positions for a population of size `n`. With
+    /// `Opposition`, the `n` opposite positions are appended.
+    pub fn positions<R: Rng, T: Float>(&self,
+                                       rng: &mut R,
+                                       min_max_pos: &[(T, T)],
+                                       n: usize)
+                                       -> Vec<Vec<T>> {
+        let dim = min_max_pos.len();
+        let unit = match *self {
+            Initialization::Uniform |
+            Initialization::Opposition => {
+                (0..n).map(|_| (0..dim).map(|_| rng.next_f64()).collect()).collect()
+            }
+            Initialization::LatinHypercube => latin_hypercube(rng, dim, n),
+            Initialization::Sobol if dim <= SOBOL_MAX_DIM => sobol(rng, dim, n),
+            // the direction numbers only cover SOBOL_MAX_DIM dimensions.
+            Initialization::Halton |
+            Initialization::Sobol => halton(rng, dim, n),
+        };
+
+        let mut positions: Vec<Vec<T>> = unit.iter()
+            .map(|u: &Vec<f64>| {
+                u.iter()
+                    .zip(min_max_pos.iter())
+                    .map(|(&u, &(min, max))| min + T::from_f64(u * (max - min).to_f64()))
+                    .collect()
+            })
+            .collect();
+
+        if let Initialization::Opposition = *self {
+            let opposites: Vec<Vec<T>> = positions.iter()
+                .map(|pos| {
+                    pos.iter()
+                        .zip(min_max_pos.iter())
+                        .map(|(&x, &(min, max))| min + max - x)
+                        .collect()
+                })
+                .collect();
+            positions.extend(opposites);
+        }
+        positions
+    }
+}
+
+fn latin_hypercube<R: Rng>(rng: &mut R, dim: usize, n: usize) -> Vec<Vec<f64>> {
+    let mut points = vec![vec![0.0; dim]; n];
+    for d in 0..dim {
+        // Fisher-Yates shuffle of the slices.
+        let mut slices: Vec<usize> = (0..n).collect();
+        for i in (1..n).rev() {
+            let j = Range::new(0, i + 1).ind_sample(rng);
+            slices.swap(i, j);
+        }
+        for (point, &slice) in points.iter_mut().zip(slices.iter()) {
+            point[d] = (slice as f64 + rng.next_f64()) / n as f64;
+        }
+    }
+    points
+}
+
+fn halton<R: Rng>(rng: &mut R, dim: usize, n: usize) -> Vec<Vec<f64>> {
+    let bases = primes(dim);
+    let shifts: Vec<f64> = (0..dim).map(|_| rng.next_f64()).collect();
+    (1..n + 1)
+        .map(|i| {
+            bases.iter()
+                .zip(shifts.iter())
+                .map(|(&base, &shift)| (radical_inverse(i, base) + shift) % 1.0)
+                .collect()
+        })
+        .collect()
+}
+
+// The digits of i in the given base, mirrored at the decimal point.
+fn radical_inverse(mut i: usize, base: usize) -> f64 {
+    let mut result = 0.0;
+    let mut fraction = 1.0 / base as f64;
+    while i > 0 {
+        result += (i % base) as f64 * fraction;
+        i /= base;
+        fraction /= base as f64;
+    }
+    result
+}
+
+fn primes(count: usize) -> Vec<usize> {
+    let mut primes = Vec::with_capacity(count);
+    let mut candidate = 2;
+    while primes.len() < count {
+        if primes.iter().all(|&p| candidate % p != 0) {
+            primes.push(candidate);
+        }
+        candidate += 1;
+    }
+    primes
+}
+
+// Primitive polynomials and initial direction numbers for the dimensions
+// after the first, from Joe and Kuo's new-joe-kuo-6.21201 table: the
+// degree s, the coefficients a, and the initial numbers m.
+const SOBOL_DIRECTIONS: [(u32, u32, &[u32]); 20] = [(1, 0, &[1]),
+                                                     (2, 1, &[1, 3]),
+                                                     (3, 1, &[1, 3, 1]),
+                                                     (3, 2, &[1, 1, 1]),
+                                                     (4, 1, &[1, 1, 3, 3]),
+                                                     (4, 4, &[1, 3, 5, 13]),
+                                                     (5, 2, &[1, 1, 5, 5, 17]),
+                                                     (5, 4, &[1, 1, 5, 5, 5]),
+                                                     (5, 7, &[1, 1, 7, 11, 19]),
+                                                     (5, 11, &[1, 1, 5, 1, 1]),
+                                                     (5, 13, &[1, 1, 1, 3, 11]),
+                                                     (5, 14, &[1, 3, 5, 5, 31]),
+                                                     (6, 1, &[1, 3, 3, 9, 7, 49]),
+                                                     (6, 13, &[1, 1, 1, 15, 21, 21]),
+                                                     (6, 16, &[1, 3, 1, 13, 27, 49]),
+                                                     (6, 19, &[1, 1, 1, 15, 7, 5]),
+                                                     (6, 22, &[1, 3, 1, 15, 13, 25]),
+                                                     (6, 25, &[1, 1, 5, 5, 19, 61]),
+                                                     (7, 1, &[1, 3, 7, 11, 23, 15, 103]),
+                                                     (7, 4, &[1, 3, 7, 13, 13, 15, 69])];
+
+const SOBOL_BITS: usize = 32;
+
+// 32 bit direction numbers of dimension d.
+fn sobol_directions(d: usize) -> Vec<u32> {
+    if d == 0 {
+        return (0..SOBOL_BITS).map(|k| 1 << (SOBOL_BITS - 1 - k)).collect();
+    }
+    let (s, a, initial) = SOBOL_DIRECTIONS[d - 1];
+    let s = s as usize;
+    let mut m: Vec<u32> = initial.to_vec();
+    for k in s..SOBOL_BITS {
+        let mut next = m[k - s] ^ (m[k - s] << s);
+        for j in 1..s {
+            if (a >> (s - 1 - j)) & 1 == 1 {
+                next ^= m[k - j] << j;
+            }
+        }
+        m.push(next);
+    }
+    m.iter()
+        .enumerate()
+        .map(|(k, &m)| m << (SOBOL_BITS - 1 - k))
+        .collect()
+}
+
+fn sobol<R: Rng>(rng: &mut R, dim: usize, n: usize) -> Vec<Vec<f64>> {
+    assert!(dim <= SOBOL_MAX_DIM,
+            "Sobol initialization supports at most {} dimensions",
+            SOBOL_MAX_DIM);
+    let directions: Vec<Vec<u32>> = (0..dim).map(sobol_directions).collect();
+    let mut x: Vec<u32> = (0..dim).map(|_| rng.next_u32()).collect();
+
+    // Gray code order. Thanks to the shift, the first point is not at 0, so
+    // it needn't be skipped, and the first 2^k points stay stratified.
+    (0..n)
+        .map(|i| {
+            let point = x.iter().map(|&x| x as f64 / 4294967296.0).collect();
+            let c = (!i).trailing_zeros() as usize;
+            for (x, v) in x.iter_mut().zip(directions.iter()) {
+                *x ^= v[c % SOBOL_BITS];
+            }
+            point
+        })
+        .collect()
+}
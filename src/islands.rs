@@ -0,0 +1,276 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Island model with migration between populations.
+
+use std::cmp::Ordering;
+use rand::Rng;
+use rand::distributions::{IndependentSample, Range};
+use super::{compare, Float, Population, Settings};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Which islands receive the best individual of an island.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Topology {
+    /// Island `i` sends to island `i + 1`, the last one to the first.
+    Ring,
+
+    /// Each island sends to all other islands.
+    FullyConnected,
+
+    /// Each island sends to one other island, chosen randomly at each
+    /// migration.
+    Random,
+}
+
+/// When and where individuals migrate between islands.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Migration {
+    /// The best individual of each island migrates after every this many
+    /// generations.
+    pub interval: usize,
+
+    /// The receiving islands.
+    pub topology: Topology,
+}
+
+impl Default for Migration {
+    /// Migration along a ring every 10 generations.
+    fn default() -> Migration {
+        Migration {
+            interval: 10,
+            topology: Topology::Ring,
+        }
+    }
+}
+
+/// Several populations that evolve independently, and periodically send
+/// their best individual to other islands. This keeps more diversity than
+/// a single large population, which helps on multimodal problems. Each
+/// island can have different settings, e.g. different mutation
+/// strategies, as long as the types are the same.
+///
+/// A migrant replaces the worst individual of the receiving island, like
+/// `Population::inject`, without evaluating it again.
+///
+/// ```
+/// use differential_evolution::{mutation, Islands, Migration, Settings};
+///
+/// let cost = |pos: &[f32]| pos.iter().fold(0.0, |sum, x| sum + x * x);
+/// let settings = (0..4)
+///     .map(|i| {
+///         let mut s = Settings::default(vec![(-10.0, 10.0); 3], cost);
+///         s.pop_size = 25;
+///         if i % 2 == 1 {
+///             s.mutation = Box::new(mutation::Best1);
+///         }
+///         s
+///     })
+///     .collect();
+/// let mut islands = Islands::new(settings, Migration::default());
+/// islands.generations().nth(100);
+/// let (cost, pos) = islands.best().unwrap();
+/// ```
+///
+/// With the `parallel` feature, `par_eval_generation()` evolves the islands
+/// on separate threads between migrations.
+pub struct Islands<F, R, C, T = f32>
+    where F: Fn(&[T]) -> C,
+          R: Rng,
+          C: PartialOrd + Clone,
+          T: Float
+{
+    islands: Vec<Population<F, R, C, T>>,
+    migration: Migration,
+    generation: usize,
+}
+
+impl<F, R, C, T> Islands<F, R, C, T>
+    where F: Fn(&[T]) -> C,
+          R: Rng,
+          C: PartialOrd + Clone,
+          T: Float
+{
+    /// Creates one island for each of the settings.
+    pub fn new(settings: Vec<Settings<F, R, C, T>>, migration: Migration) -> Islands<F, R, C, T> {
+        assert!(!settings.is_empty(), "Islands need at least one island");
+        assert!(migration.interval > 0, "migration interval must be positive");
+        let dim = settings[0].min_max_pos.len();
+        assert!(settings.iter().all(|s| s.min_max_pos.len() == dim),
+                "all islands need to have the same dimension");
+        Islands {
+            islands: settings.into_iter().map(Population::new).collect(),
+            migration,
+            generation: 0,
+        }
+    }
+
+    /// Evaluates one generation on each island, one after another, and
+    /// lets the best individuals migrate when it is time.
+    pub fn eval_generation(&mut self) {
+        for island in &mut self.islands {
+            eval_island_generation(island);
+        }
+        self.finish_generation();
+    }
+
+    // Counts the generation, and migrates if it is time.
+    fn finish_generation(&mut self) {
+        self.generation += 1;
+        if self.generation % self.migration.interval == 0 && self.islands.len() > 1 {
+            self.migrate();
+        }
+    }
+
+    // Sends the best individual of every island to its neighbours. The
+    // migrants are taken before any island receives one.
+    fn migrate(&mut self) {
+        let migrants: Vec<Option<(Vec<T>, C)>> = self.islands
+            .iter()
+            .map(|island| island.best().map(|(cost, pos)| (pos.to_vec(), cost.clone())))
+            .collect();
+        let n = self.islands.len();
+        for (i, migrant) in migrants.into_iter().enumerate() {
+            let (pos, cost) = match migrant {
+                Some(migrant) => migrant,
+                None => continue,
+            };
+            let targets: Vec<usize> = match self.migration.topology {
+                Topology::Ring => vec![(i + 1) % n],
+                Topology::FullyConnected => (0..n).filter(|&j| j != i).collect(),
+                Topology::Random => {
+                    // any island except i.
+                    let j = Range::new(0, n - 1).ind_sample(&mut self.islands[i].settings.rng);
+                    vec![if j >= i { j + 1 } else { j }]
+                }
+            };
+            for j in targets {
+                self.islands[j].inject(pos.clone(), Some(cost.clone()));
+            }
+        }
+    }
+
+    // Index of the island with the best solution.
+    fn best_island(&self) -> Option<usize> {
+        let mut best: Option<usize> = None;
+        for (i, island) in self.islands.iter().enumerate() {
+            if island.best_idx.is_none() {
+                continue;
+            }
+            let is_better = match best {
+                None => true,
+                Some(b) => {
+                    let b = &self.islands[b];
+                    compare(&island.best_cost_cache,
+                            &island.best_constraints_cache,
+                            &b.best_cost_cache,
+                            &b.best_constraints_cache,
+                            b.tolerance()) == Some(Ordering::Less)
+                }
+            };
+            if is_better {
+                best = Some(i);
+            }
+        }
+        best
+    }
+
+    /// Gets a tuple of the best cost and best position found so far on any
+    /// island.
+    pub fn best(&self) -> Option<(&C, &[T])> {
+        self.best_island().and_then(|i| self.islands[i].best())
+    }
+
+    /// Gets all islands.
+    pub fn islands(&self) -> &[Population<F, R, C, T>] {
+        &self.islands
+    }
+
+    /// Number of generations that have been evaluated on each island.
+    pub fn num_generations(&self) -> usize {
+        self.generation
+    }
+
+    /// Gets the total number of cost evaluations of all islands. Failed
+    /// evaluations are not included.
+    pub fn num_cost_evaluations(&self) -> usize {
+        self.islands.iter().map(|island| island.num_cost_evaluations).sum()
+    }
+
+    /// Gets the number of failed evaluations of all islands, including the
+    /// retried ones.
+    pub fn num_failed_evaluations(&self) -> usize {
+        self.islands.iter().map(|island| island.num_failed_evaluations).sum()
+    }
+
+    /// Gets an iterator over the generations of the islands. Unlike
+    /// `Population::iter()`, each call to `next()` evaluates a whole
+    /// generation on each island, and returns the best cost.
+    pub fn generations(&mut self) -> IslandsGenerations<'_, F, R, C, T> {
+        IslandsGenerations { islands: self }
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<F, R, C, T> Islands<F, R, C, T>
+    where F: Fn(&[T]) -> C + Send,
+          R: Rng + Send,
+          C: PartialOrd + Clone + Send,
+          T: Float
+{
+    /// Like `eval_generation()`, but each island evolves on its own thread
+    /// with rayon. The results are exactly the same as with
+    /// `eval_generation()`.
+    ///
+    /// Only available with the `parallel` feature.
+    pub fn par_eval_generation(&mut self) {
+        self.islands.par_iter_mut().for_each(eval_island_generation);
+        self.finish_generation();
+    }
+}
+
+// Evaluates the next generation of the island.
+fn eval_island_generation<F, R, C, T>(island: &mut Population<F, R, C, T>)
+    where F: Fn(&[T]) -> C,
+          R: Rng,
+          C: PartialOrd + Clone,
+          T: Float
+{
+    island.eval();
+    while 0 != island.pop_countdown {
+        island.eval();
+    }
+}
+
+/// Iterator for `Islands`, to evaluate one generation on each island every
+/// time `next()` is called.
+pub struct IslandsGenerations<'a, F, R, C, T = f32>
+    where F: 'a + Fn(&[T]) -> C,
+          R: 'a + Rng,
+          C: 'a + PartialOrd + Clone,
+          T: 'a + Float
+{
+    islands: &'a mut Islands<F, R, C, T>,
+}
+
+impl<'a, F, R, C, T> Iterator for IslandsGenerations<'a, F, R, C, T>
+    where F: 'a + Fn(&[T]) -> C,
+          R: 'a + Rng,
+          C: PartialOrd + Clone,
+          T: 'a + Float
+{
+    type Item = C;
+
+    /// Forwards to `eval_generation()`, and returns the best cost of all
+    /// islands.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.islands.eval_generation();
+        self.islands.best().map(|(cost, _)| cost.clone())
+    }
+}
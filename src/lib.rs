@@ -121,6 +121,21 @@
 //! de.iter().take(100000).find(|&cost| cost < 0.1);
 //! ```
 //!
+//! More elaborate criteria like stagnation, time limits or convergence of
+//! the population are available in the `termination` module, for use with
+//! `run_until`:
+//!
+//! ```
+//! # use differential_evolution::self_adaptive_de;
+//! # use differential_evolution::termination::{Any, MaxEvaluations, Stagnation};
+//! # fn sum_of_squares(pos: &[f32]) -> f32 { 0.0 }
+//! # let initial_min_max = vec![(-5.12, 5.12); 2];
+//! let mut de = self_adaptive_de(initial_min_max, sum_of_squares);
+//! let reason = de.run_until(Any(vec![Box::new(MaxEvaluations(100000)),
+//!                                    Box::new(Stagnation::new(100))]));
+//! println!("stopped because of {:?}", reason);
+//! ```
+//!
 //! When you are finished with iterating, you can extract the best solution found so far with
 //! `de.best()`. This retrieves the minimum cost and the position vector that has lead to this
 //! cost:
@@ -136,6 +151,113 @@
 //! println!("{:?} best position", pos);
 //! ```
 //!
+//! By default, trials replace their parents after each generation. With
+//! `Settings::updating` set to `Updating::Immediate`, they replace them
+//! right after their evaluation, which often converges faster.
+//!
+//! ### Precision
+//!
+//! Positions can be either `f32` or `f64`, the type is inferred from the
+//! cost function. `f32` is faster, but stalls at a relative precision of
+//! about 1e-7. Closures without a type annotation, like the one in the
+//! quick start example, fall back to `f64`. Versions up to 0.2 always used
+//! `f32`; annotate the argument as `&[f32]` to keep that. Use `f64` when
+//! you need more precision:
+//!
+//! ```
+//! # use differential_evolution::self_adaptive_de;
+//! let mut de = self_adaptive_de(vec![(-1.0, 1.0); 3], |pos: &[f64]| {
+//!     pos.iter().fold(0.0, |sum, x| sum + (x - 0.1) * (x - 0.1))
+//! });
+//! de.iter().nth(20000);
+//! ```
+//!
+//! ### Reproducibility
+//!
+//! By default the random number generator is seeded randomly. With a
+//! seed, runs are reproducible:
+//!
+//! ```
+//! use differential_evolution::{Population, Settings};
+//!
+//! let cost = |pos: &[f32]| pos.iter().fold(0.0, |sum, x| sum + x * x);
+//! let mut de = Population::new(Settings::default(vec![(-10.0, 10.0); 3], cost).with_seed(42));
+//! de.iter().nth(1000);
+//! ```
+//!
+//! Identical settings with the same seed and a deterministic cost function
+//! produce identical trajectories: the same positions are evaluated in the
+//! same order, and the same best solution is found. This also holds for
+//! `ask()`/`tell()`, `eval_generation()`, and runs resumed from a
+//! `Checkpoint`. Results may differ between versions of this crate, and
+//! between platforms with different floating point math libraries.
+//!
+//! ### Parallel Evaluation
+//!
+//! For expensive cost functions, enable the `parallel` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! differential-evolution = { version = "*", features = ["parallel"] }
+//! ```
+//!
+//! Then `Population::eval_generation()` evaluates a whole generation
+//! concurrently with [rayon](https://github.com/rayon-rs/rayon). The cost
+//! function has to be `Sync`.
+//!
+//! `Islands` evolve several populations, and with the `parallel` feature
+//! `Islands::par_eval_generation()` evolves each of them on its own thread.
+//!
+//! ### Checkpoints
+//!
+//! With the `serde` feature, a `Checkpoint` of the population can be
+//! serialized, and the run can later be resumed exactly where it stopped
+//! with `Population::resume`. See `Checkpoint` for an example.
+//!
+//! ### Local Search
+//!
+//! DE finds the region of the optimum quickly, but converges slowly within
+//! it. `Population::polish` refines the best solution with a Nelder-Mead
+//! or compass search, like scipy's `polish` option:
+//!
+//! ```
+//! # use differential_evolution::{self_adaptive_de, LocalSearch};
+//! # fn sum_of_squares(pos: &[f32]) -> f32 { 0.0 }
+//! # let initial_min_max = vec![(-5.12, 5.12); 2];
+//! let mut de = self_adaptive_de(initial_min_max, sum_of_squares);
+//! de.iter().nth(1000);
+//! de.polish(LocalSearch::NelderMead, 200);
+//! ```
+//!
+//! With `Settings::memetic`, the local search also runs periodically
+//! during the optimization.
+//!
+//! ### Coming from scipy
+//!
+//! `minimize` mirrors scipy's `differential_evolution`: one call with the
+//! same options and defaults, returning an `OptimizeResult`.
+//!
+//! ### Noisy Cost Functions
+//!
+//! When the cost function is noisy, e.g. a Monte-Carlo simulation, set
+//! `Settings::noise` to a `NoiseHandling`. Survivors are then re-evaluated
+//! and their costs averaged, so that a single lucky evaluation does not
+//! become the best solution.
+//!
+//! ### Integer and Categorical Variables
+//!
+//! `Settings::mixed` takes a `SearchSpace` in which each dimension is
+//! continuous, an integer, or one of several categories. Trials are
+//! rounded to valid values, and `Population::best_values` decodes the best
+//! position into typed `Value`s.
+//!
+//! ### Restarts
+//!
+//! On multimodal problems the population may converge to a local optimum.
+//! `Restarts` wraps a population and restarts it with fresh random
+//! individuals when it stagnates, optionally with a growing population
+//! (IPOP). See `RestartStrategy`.
+//!
 //! # Similar Crates
 //!
 //! - [darwin-rs](https://github.com/willi-kappler/darwin-rs)
@@ -143,21 +265,86 @@
 //!
 
 extern crate rand;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
 
+pub mod crossover;
+pub mod mutation;
+pub mod termination;
+mod adaptation;
+mod boundary;
+mod checkpoint;
+mod constraint;
+mod fallible;
+mod float;
+mod initialization;
+mod islands;
+mod local_search;
+mod minimize;
+mod multi_objective;
+mod noise;
+mod restart;
+mod rng;
+mod search_space;
+mod status;
+mod view;
+
+pub use adaptation::Adaptation;
+pub use boundary::BoundaryHandling;
+pub use checkpoint::Checkpoint;
+pub use constraint::{Constraint, ConstraintFunction, ConstraintHandling};
+pub use fallible::Fallible;
+pub use float::Float;
+pub use initialization::Initialization;
+pub use islands::{Islands, IslandsGenerations, Migration, Topology};
+pub use local_search::{LocalSearch, Memetic};
+pub use minimize::{minimize, MinimizeOptions, OptimizeResult};
+pub use multi_objective::{Gde3, Gde3Iter};
+pub use noise::NoiseHandling;
+pub use restart::{RestartStatistics, RestartStrategy, Restarts, RestartsIter};
+pub use rng::XorShift128;
+pub use search_space::{SearchSpace, Value, Variable};
+pub use status::{Observer, Statistics, Status};
+pub use view::IndividualView;
+
+use std::cmp::Ordering;
 use rand::distributions::{IndependentSample, Range};
+use adaptation::SuccessMemory;
+use constraint::total_violation;
+use crossover::Crossover;
+use fallible::is_valid_cost;
+use noise::Samples;
+use mutation::{Donors, MutationStrategy};
+use termination::{Reason, Termination};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 /// Holds all settings for the self adaptive differential evolution
 /// algorithm.
-pub struct Settings<F, R, C>
-    where F: Fn(&[f32]) -> C,
+pub struct Settings<F, R, C, T = f32>
+    where F: Fn(&[T]) -> C,
           R: rand::Rng,
-          C: PartialOrd + Clone
+          C: PartialOrd + Clone,
+          T: Float
 {
     /// The population is initialized with uniform random
     /// for each dimension between the tuple's size.
     /// Beware that this is only the initial state, the DE
-    /// will search outside of this initial search space.
-    pub min_max_pos: Vec<(f32, f32)>,
+    /// will search outside of this initial search space unless
+    /// `bounds` are set.
+    pub min_max_pos: Vec<(T, T)>,
+
+    /// Optional hard bounds for each dimension. When set, the DE never
+    /// evaluates a position outside of these bounds, which is necessary
+    /// when the cost function is undefined there. `min_max_pos` has to lie
+    /// within the bounds.
+    pub bounds: Option<Vec<(T, T)>>,
+
+    /// How a trial vector that has left the `bounds` is repaired.
+    pub boundary_handling: BoundaryHandling,
 
     /// Minimum and maximum value for `cr`, the crossover control parameter.
     /// a good value is (0, 1) so cr is randomly choosen between in the full
@@ -180,28 +367,104 @@ pub struct Settings<F, R, C>
     /// `cr_change_probability`, 0.1 is a reasonable choice.
     pub f_change_probability: f32,
 
+    /// How `cr` and `f` are adapted. The jDE parameters above are only
+    /// used with `Adaptation::Jde`.
+    pub adaptation: Adaptation,
+
+    /// Maximum number of replaced parents that are kept in an external
+    /// archive. The last random individual of the mutation strategy is
+    /// chosen from the population together with the archive, which
+    /// improves diversity. JADE uses the population size, 0 disables the
+    /// archive. When the population shrinks, the archive shrinks
+    /// proportionally.
+    pub archive_size: usize,
+
+    /// Measures by how much the cost of an improved individual is lower
+    /// than the cost of its parent. SHADE weights the successful parameters
+    /// by this. With `None` all are weighted equally.
+    pub improvement: Option<fn(&C, &C) -> f64>,
+
     /// Number of individuals for the DE. In many benchmarks, a size of
     /// 100 is used. The choice somewhat depends on the difficulty and the
     /// dimensionality of the  problem to solve. Reasonable choices seem
     /// between 20 and 200.
     pub pop_size: usize,
 
+    /// Linearly reduces the population size from `pop_size` down to a final
+    /// size over a budget of cost evaluations, as in L-SHADE. The worst
+    /// individuals are removed. `None` keeps the population size fixed.
+    pub pop_reduction: Option<LinearReduction>,
+
+    /// The mutation strategy used to create the mutant vector of each
+    /// individual. `mutation::Rand1` (DE/rand/1) is the most widely used
+    /// one. Exploitative strategies like `mutation::Best1` converge
+    /// faster, but are more likely to get stuck in a local optimum.
+    pub mutation: Box<dyn MutationStrategy<T>>,
+
+    /// The crossover operator that combines the mutant with the individual.
+    /// `crossover::Binomial` is the standard choice. `crossover::Exponential`
+    /// often works better when neighbouring variables are strongly linked.
+    pub crossover: Box<dyn Crossover<T>>,
+
+    /// How the initial positions are distributed within `min_max_pos`.
+    pub initialization: Initialization,
+
+    /// Known good positions, e.g. from previous runs, that replace random
+    /// positions of the initial population. Candidates with a known cost
+    /// are not evaluated again. See also `Population::inject`.
+    pub initial_candidates: Vec<(Vec<T>, Option<C>)>,
+
     /// Random number generator used to generate mutations. If the fitness
     /// function is fairly fast, the random number generator should be
     /// very fast as well. Since it is not necessary to use a cryptographic
     /// secure RNG, the best (fastest) choice is to use `rand::weak_rng()`.
     pub rng: R,
 
-    /// The cost function to minimize. This takes an `&[f32]` and returns
+    /// The cost function to minimize. This takes an `&[T]` and returns
     /// the calculated cost for this position as `C`. This should be
     /// fast to evaluate, and always produce the same result for the same
-    /// input.
+    /// input. Costs that are not comparable to themselves, like NaN, are
+    /// treated as failed evaluations, see `Fallible`.
     pub cost_function: F,
+
+    /// How often a failed evaluation is retried before the position gets
+    /// the failed cost. Only useful when failures are not deterministic.
+    pub max_retries: usize,
+
+    /// Optional constraints for constrained optimization. Evaluated for
+    /// each position together with the cost function, it returns the values
+    /// of all inequality constraints `g(x) <= 0` and equality constraints
+    /// `h(x) = 0`. Individuals are then compared with Deb's feasibility
+    /// rules, see `ConstraintHandling`.
+    pub constraints: Option<ConstraintFunction<T>>,
+
+    /// How individuals that violate the `constraints` are compared.
+    pub constraint_handling: ConstraintHandling,
+
+    /// Optional callback that is invoked after each generation has been
+    /// evaluated, with the status of the population.
+    pub observer: Option<Observer<C, T>>,
+
+    /// Optional periodic local search on the best individual, see
+    /// `Population::polish`. It uses the cost function, so it does not
+    /// work with `ask()` and `tell()`.
+    pub memetic: Option<Memetic>,
+
+    /// Optional handling of noisy cost functions, see `NoiseHandling`.
+    pub noise: Option<NoiseHandling<C>>,
+
+    /// Whether trials replace their parents after each generation, or
+    /// immediately after their evaluation.
+    pub updating: Updating,
+
+    /// Optional integer and categorical variables, see `SearchSpace`.
+    pub search_space: Option<SearchSpace>,
 }
 
-impl<F, C> Settings<F, rand::XorShiftRng, C>
-    where F: Fn(&[f32]) -> C,
-          C: PartialOrd + Clone
+impl<F, C, T> Settings<F, rand::XorShiftRng, C, T>
+    where F: Fn(&[T]) -> C,
+          C: PartialOrd + Clone,
+          T: Float
 {
     /// Creates default settings for the differential evolution. It uses the default
     /// parameters as defined in the paper "Self-Adapting Control Parameters in Differential
@@ -210,11 +473,13 @@ impl<F, C> Settings<F, rand::XorShiftRng, C>
     /// generator available.
     ///
     /// For most problems this should be a fairly good parameter set.
-    pub fn default(min_max_pos: Vec<(f32, f32)>,
+    pub fn default(min_max_pos: Vec<(T, T)>,
                    cost_function: F)
-                   -> Settings<F, rand::XorShiftRng, C> {
+                   -> Settings<F, rand::XorShiftRng, C, T> {
         Settings {
-            min_max_pos: min_max_pos,
+            min_max_pos,
+            bounds: None,
+            boundary_handling: BoundaryHandling::Midpoint,
 
             cr_min_max: (0.0, 1.0),
             cr_change_probability: 0.1,
@@ -222,49 +487,258 @@ impl<F, C> Settings<F, rand::XorShiftRng, C>
             f_min_max: (0.1, 1.0),
             f_change_probability: 0.1,
 
+            adaptation: Adaptation::Jde,
+            archive_size: 0,
+            improvement: None,
+
             pop_size: 100,
+            pop_reduction: None,
+            mutation: Box::new(mutation::Rand1),
+            crossover: Box::new(crossover::Binomial),
+            initialization: Initialization::Uniform,
+            initial_candidates: Vec::new(),
             rng: rand::weak_rng(),
 
-            cost_function: cost_function,
+            cost_function,
+            max_retries: 0,
+
+            constraints: None,
+            constraint_handling: ConstraintHandling::Feasibility { tolerance: 1e-4 },
+            observer: None,
+            memetic: None,
+            noise: None,
+            updating: Updating::Deferred,
+            search_space: None,
+        }
+    }
+
+    /// Creates settings for JADE, as described in "JADE: Adaptive
+    /// Differential Evolution With Optional External Archive": adaptive
+    /// `cr` and `f`, DE/current-to-pbest/1 mutation with `p = 0.05`, and an
+    /// archive as large as the population.
+    pub fn jade(min_max_pos: Vec<(T, T)>,
+                cost_function: F)
+                -> Settings<F, rand::XorShiftRng, C, T> {
+        let mut s = Settings::default(min_max_pos, cost_function);
+        s.adaptation = Adaptation::Jade { c: 0.1 };
+        s.mutation = Box::new(mutation::CurrentToPBest1 { p: 0.05 });
+        s.archive_size = s.pop_size;
+        s
+    }
+
+    /// Creates default settings for a search space with integer and
+    /// categorical variables. Positions never leave the `SearchSpace`.
+    pub fn mixed(search_space: SearchSpace, cost_function: F) -> Settings<F, rand::XorShiftRng, C, T> {
+        let bounds = search_space.bounds();
+        let mut s = Settings::default(bounds.clone(), cost_function);
+        s.bounds = Some(bounds);
+        s.search_space = Some(search_space);
+        s
+    }
+}
+
+impl<F, C, T> Settings<F, rand::XorShiftRng, C, T>
+    where F: Fn(&[T]) -> C,
+          C: PartialOrd + Clone + Into<f64>,
+          T: Float
+{
+    /// Creates settings for L-SHADE, as described in "Improving the Search
+    /// Performance of SHADE Using Linear Population Size Reduction": SHADE
+    /// adaptation with a memory size of 6, DE/current-to-pbest/1 mutation
+    /// with `p = 0.11`, and an archive of 2.6 times the population size. The
+    /// population starts with `18 * dim` individuals and is linearly reduced
+    /// to 4 individuals when `max_cost_evaluations` have been performed.
+    pub fn l_shade(min_max_pos: Vec<(T, T)>,
+                   cost_function: F,
+                   max_cost_evaluations: usize)
+                   -> Settings<F, rand::XorShiftRng, C, T> {
+        let mut s = Settings::default(min_max_pos, cost_function);
+        s.adaptation = Adaptation::Shade { memory_size: 6 };
+        s.mutation = Box::new(mutation::CurrentToPBest1 { p: 0.11 });
+        s.improvement = Some(cost_difference::<C>);
+        s.pop_size = 18 * s.min_max_pos.len();
+        s.archive_size = (2.6 * s.pop_size as f32).round() as usize;
+        s.pop_reduction = Some(LinearReduction {
+            final_pop_size: 4,
+            max_cost_evaluations,
+        });
+        s
+    }
+}
+
+impl<F, R, C, T> Settings<F, R, C, T>
+    where F: Fn(&[T]) -> C,
+          R: rand::Rng,
+          C: PartialOrd + Clone,
+          T: Float
+{
+    /// Uses a `XorShift128` generator seeded with `seed`, to make the
+    /// optimization reproducible. See the crate documentation for what is
+    /// guaranteed.
+    pub fn with_seed(self, seed: u64) -> Settings<F, XorShift128, C, T> {
+        self.with_rng(XorShift128::seed_from_u64(seed))
+    }
+
+    /// Replaces the random number generator, e.g. with a `XorShift128` so
+    /// that the population can be checkpointed.
+    pub fn with_rng<R2: rand::Rng>(self, rng: R2) -> Settings<F, R2, C, T> {
+        Settings {
+            min_max_pos: self.min_max_pos,
+            bounds: self.bounds,
+            boundary_handling: self.boundary_handling,
+            cr_min_max: self.cr_min_max,
+            cr_change_probability: self.cr_change_probability,
+            f_min_max: self.f_min_max,
+            f_change_probability: self.f_change_probability,
+            adaptation: self.adaptation,
+            archive_size: self.archive_size,
+            improvement: self.improvement,
+            pop_size: self.pop_size,
+            pop_reduction: self.pop_reduction,
+            mutation: self.mutation,
+            crossover: self.crossover,
+            initialization: self.initialization,
+            initial_candidates: self.initial_candidates,
+            rng,
+            cost_function: self.cost_function,
+            max_retries: self.max_retries,
+            constraints: self.constraints,
+            constraint_handling: self.constraint_handling,
+            observer: self.observer,
+            memetic: self.memetic,
+            noise: self.noise,
+            updating: self.updating,
+            search_space: self.search_space,
         }
     }
 }
 
+fn cost_difference<C>(parent: &C, trial: &C) -> f64
+    where C: Clone + Into<f64>
+{
+    parent.clone().into() - trial.clone().into()
+}
+
+/// Linear population size reduction, see `Settings::pop_reduction`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinearReduction {
+    /// Population size when the budget is used up.
+    pub final_pop_size: usize,
+
+    /// Number of cost evaluations after which the population has reached
+    /// its final size.
+    pub max_cost_evaluations: usize,
+}
+
+/// When trials replace their parents, see `Settings::updating`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Updating {
+    /// All trials of a generation are created from the previous
+    /// generation, and replace their parents once the whole generation is
+    /// evaluated. This is the classic DE.
+    Deferred,
+
+    /// Steady-state DE: each trial replaces its parent right after its
+    /// evaluation, and each trial is created just before its evaluation
+    /// from the latest population. Improvements are used as donors
+    /// immediately, which often converges faster on unimodal problems. Only
+    /// works with `eval()`, not with `ask()`/`tell()` or
+    /// `eval_generation()`.
+    Immediate,
+}
+
 /// Internally used struct for an inivididual.
 #[derive(Clone)]
-struct Individual<C>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Individual<C, T>
     where C: PartialOrd + Clone
 {
-    pos: Vec<f32>,
+    pos: Vec<T>,
     // the lower, the better.
     cost: Option<C>,
 
     // control parameters
     cr: f32,
     f: f32,
+
+    // values of the constraints, empty if unconstrained.
+    constraints: Vec<Constraint<T>>,
+
+    // cost samples, only used with noise handling.
+    samples: Samples,
+}
+
+// Compares two evaluated individuals by cost and constraints, `Less` means
+// that `a` is better. Failed evaluations are always worst. With a
+// tolerance, Deb's feasibility rules are applied: feasible beats
+// infeasible, and of two infeasible the one with the lower total violation
+// is better.
+fn compare<C, T>(a_cost: &Option<C>,
+                 a_constraints: &[Constraint<T>],
+                 b_cost: &Option<C>,
+                 b_constraints: &[Constraint<T>],
+                 tolerance: Option<f32>)
+                 -> Option<Ordering>
+    where C: PartialOrd,
+          T: Float
+{
+    // failed evaluations are worse than anything else.
+    match (is_valid_cost(a_cost), is_valid_cost(b_cost)) {
+        (true, false) => return Some(Ordering::Less),
+        (false, true) => return Some(Ordering::Greater),
+        (false, false) => return Some(Ordering::Equal),
+        (true, true) => {}
+    }
+    if let Some(tolerance) = tolerance {
+        let a_violation = total_violation(a_constraints, tolerance);
+        let b_violation = total_violation(b_constraints, tolerance);
+        if a_violation > 0.0 || b_violation > 0.0 {
+            return a_violation.partial_cmp(&b_violation);
+        }
+    }
+    a_cost.partial_cmp(b_cost)
+}
+
+// True if there are no constraints, or if all of them are satisfied.
+fn is_feasible<T: Float>(constraints: &[Constraint<T>], tolerance: Option<f32>) -> bool {
+    tolerance.map_or(true, |tolerance| total_violation(constraints, tolerance) == 0.0)
 }
 
 /// Holds the population for the differential evolution based on the given settings.
-pub struct Population<F, R, C>
-    where F: Fn(&[f32]) -> C,
+pub struct Population<F, R, C, T = f32>
+    where F: Fn(&[T]) -> C,
           R: rand::Rng,
-          C: PartialOrd + Clone
+          C: PartialOrd + Clone,
+          T: Float
 {
-    curr: Vec<Individual<C>>,
-    best: Vec<Individual<C>>,
+    curr: Vec<Individual<C, T>>,
+    best: Vec<Individual<C, T>>,
+
+    // positions of replaced parents, used as donors.
+    archive: Vec<Vec<T>>,
+
+    // memory of the success based adaptation.
+    memory: SuccessMemory,
 
-    settings: Settings<F, R, C>,
+    settings: Settings<F, R, C, T>,
 
     // index of global best individual. Might be in best or in curr.
     best_idx: Option<usize>,
 
     // cost value of the global best individual, for quick access
     best_cost_cache: Option<C>,
+    best_constraints_cache: Vec<Constraint<T>>,
     num_cost_evaluations: usize,
 
-    dim: usize,
+    // number of failed evaluations, not included in num_cost_evaluations.
+    num_failed_evaluations: usize,
+
+    // num_cost_evaluations when the population was last initialized, the
+    // start of the pop_reduction budget.
+    run_start_evaluations: usize,
+    generation: usize,
+
     between_popsize: Range<usize>,
-    between_dim: Range<usize>,
     between_cr: Range<f32>,
     between_f: Range<f32>,
 
@@ -274,88 +748,376 @@ pub struct Population<F, R, C>
 
 /// Convenience function to create a fully configured self adaptive
 /// differential evolution population.
-pub fn self_adaptive_de<F, C>(min_max_pos: Vec<(f32, f32)>,
+pub fn self_adaptive_de<F, C, T>(min_max_pos: Vec<(T, T)>,
                               cost_function: F)
-                              -> Population<F, rand::XorShiftRng, C>
-    where F: Fn(&[f32]) -> C,
-          C: PartialOrd + Clone
+                              -> Population<F, rand::XorShiftRng, C, T>
+    where F: Fn(&[T]) -> C,
+          C: PartialOrd + Clone,
+          T: Float
 {
     Population::new(Settings::default(min_max_pos, cost_function))
 }
 
-impl<F, R, C> Population<F, R, C>
-    where F: Fn(&[f32]) -> C,
+/// Convenience function to create a fully configured JADE population. It
+/// has the same interface as `self_adaptive_de`.
+pub fn jade<F, C, T>(min_max_pos: Vec<(T, T)>,
+                  cost_function: F)
+                  -> Population<F, rand::XorShiftRng, C, T>
+    where F: Fn(&[T]) -> C,
+          C: PartialOrd + Clone,
+          T: Float
+{
+    Population::new(Settings::jade(min_max_pos, cost_function))
+}
+
+/// Convenience function to create a fully configured L-SHADE population,
+/// which reduces its size until `max_cost_evaluations` are done.
+pub fn l_shade<F, C, T>(min_max_pos: Vec<(T, T)>,
+                     cost_function: F,
+                     max_cost_evaluations: usize)
+                     -> Population<F, rand::XorShiftRng, C, T>
+    where F: Fn(&[T]) -> C,
+          C: PartialOrd + Clone + Into<f64>,
+          T: Float
+{
+    Population::new(Settings::l_shade(min_max_pos, cost_function, max_cost_evaluations))
+}
+
+/// Convenience function to create a fully configured multi-objective
+/// optimizer with GDE3. The cost function returns the values of all
+/// objectives to minimize.
+pub fn gde3<F, T>(min_max_pos: Vec<(T, T)>, cost_function: F) -> Gde3<F, rand::XorShiftRng, T>
+    where F: Fn(&[T]) -> Vec<T>,
+          T: Float
+{
+    Gde3::new(Settings::default(min_max_pos, cost_function))
+}
+
+impl<F, R, C, T> Population<F, R, C, T>
+    where F: Fn(&[T]) -> C,
           R: rand::Rng,
-          C: PartialOrd + Clone
+          C: PartialOrd + Clone,
+          T: Float
 {
     /// Creates a new population based on the given settings.
-    pub fn new(s: Settings<F, R, C>) -> Population<F, R, C> {
-        assert!(s.min_max_pos.len() >= 1,
+    pub fn new(s: Settings<F, R, C, T>) -> Population<F, R, C, T> {
+        assert!(!s.min_max_pos.is_empty(),
                 "need at least one element to optimize");
+        assert!(s.pop_size >= s.mutation.num_random(),
+                "pop_size too small for the mutation strategy");
+        if let Some(ref bounds) = s.bounds {
+            assert!(bounds.len() == s.min_max_pos.len(),
+                    "bounds need to have the same dimension as min_max_pos");
+            for (b, m) in bounds.iter().zip(s.min_max_pos.iter()) {
+                assert!(b.0 <= m.0 && m.1 <= b.1, "min_max_pos has to be within bounds");
+            }
+        }
+        if let Some(ref space) = s.search_space {
+            assert_eq!(space.variables().len(), s.min_max_pos.len(),
+                       "search_space needs to have the same dimension as min_max_pos");
+        }
+        if let Some(m) = s.memetic {
+            assert!(m.generations > 0, "memetic generations need to be at least 1");
+        }
+        if let Adaptation::Constant { f_min_max, .. } = s.adaptation {
+            assert!(f_min_max.0 <= f_min_max.1, "f_min_max must not be reversed");
+        }
+        if let Some(ref r) = s.pop_reduction {
+            assert!(r.final_pop_size >= s.mutation.num_random() && r.final_pop_size <= s.pop_size,
+                    "final_pop_size too small for the mutation strategy, or larger than pop_size");
+        }
 
-        // create a vector of randomly initialized individuals for current.
-        let dim = s.min_max_pos.len();
-
-        // Empty individual, with no cost value (yet)
-        let dummy_individual = Individual {
-            pos: vec![0.0; dim],
-            cost: None,
-            cr: 0.0,
-            f: 0.0,
-        };
-
-        // creates all the empty individuals
         let mut pop = Population {
-            curr: vec![dummy_individual.clone(); s.pop_size],
-            best: vec![dummy_individual; s.pop_size],
+            curr: Vec::new(),
+            best: Vec::new(),
+            archive: Vec::new(),
+            memory: SuccessMemory::new(&s.adaptation),
             best_idx: None,
             best_cost_cache: None,
+            best_constraints_cache: Vec::new(),
             num_cost_evaluations: 0,
-            dim: dim,
-            pop_countdown: s.pop_size,
+            num_failed_evaluations: 0,
+            run_start_evaluations: 0,
+            generation: 0,
+            pop_countdown: 0,
             between_popsize: Range::new(0, s.pop_size),
-            between_dim: Range::new(0, dim),
             between_cr: Range::new(s.cr_min_max.0, s.cr_min_max.1),
             between_f: Range::new(s.f_min_max.0, s.f_min_max.1),
             settings: s,
         };
+        pop.initialize();
+        pop
+    }
+
+    // Creates all individuals with their initial positions and control
+    // parameters, and resets everything except the evaluation counters.
+    fn initialize(&mut self) {
+        // initial positions for current, twice as many with opposition.
+        let dim = self.settings.min_max_pos.len();
+        let positions = self.settings.initialization.positions(&mut self.settings.rng,
+                                                                &self.settings.min_max_pos,
+                                                                self.settings.pop_size);
+        let size = positions.len();
+        let mut candidates = std::mem::take(&mut self.settings.initial_candidates);
+        assert!(candidates.len() <= size, "more initial_candidates than individuals");
+        for candidate in &mut candidates {
+            self.prepare_candidate(&mut candidate.0);
+        }
+
+        // Empty individual, with no cost value (yet)
+        let dummy_individual = Individual {
+            pos: vec![T::from_f64(0.0); dim],
+            cost: None,
+            cr: 0.0,
+            f: 0.0,
+            constraints: Vec::new(),
+            samples: Samples::default(),
+        };
+
+        // creates all the empty individuals
+        self.curr = vec![dummy_individual.clone(); size];
+        self.best = vec![dummy_individual; size];
+        self.archive.clear();
+        self.memory = SuccessMemory::new(&self.settings.adaptation);
+        self.best_idx = None;
+        self.best_cost_cache = None;
+        self.best_constraints_cache.clear();
+        self.generation = 0;
+        self.run_start_evaluations = self.num_cost_evaluations;
+        self.pop_countdown = size;
+        self.between_popsize = Range::new(0, size);
+
+        for (ind, pos) in self.curr.iter_mut().zip(positions) {
+            ind.pos = pos;
 
-        for ind in &mut pop.curr {
             // init control parameters
-            ind.cr = pop.between_cr.ind_sample(&mut pop.settings.rng);
-            ind.f = pop.between_f.ind_sample(&mut pop.settings.rng);
+            match self.settings.adaptation {
+                Adaptation::Jde => {
+                    ind.cr = self.between_cr.ind_sample(&mut self.settings.rng);
+                    ind.f = self.between_f.ind_sample(&mut self.settings.rng);
+                }
+                Adaptation::Jade { .. } |
+                Adaptation::Shade { .. } => {
+                    let (cr, f) = self.memory.sample(&mut self.settings.rng);
+                    ind.cr = cr;
+                    ind.f = f;
+                }
+                Adaptation::Constant { .. } => {}
+            }
+        }
+        self.draw_constant_parameters();
 
-            // random range for each dimension
-            for d in 0..dim {
-                let between_min_max = Range::new(pop.settings.min_max_pos[d].0,
-                                                 pop.settings.min_max_pos[d].1);
-                ind.pos[d] = between_min_max.ind_sample(&mut pop.settings.rng);
+        // candidates with a known cost go to the end, which is not evaluated.
+        // their costs are not counted as evaluations.
+        let (known, unknown): (Vec<_>, Vec<_>) =
+            candidates.into_iter().partition(|c| c.1.is_some());
+        for (ind, (pos, _)) in self.curr.iter_mut().zip(unknown) {
+            ind.pos = pos;
+        }
+        if let Some(ref space) = self.settings.search_space {
+            for ind in &mut self.curr[..size - known.len()] {
+                space.round(&mut ind.pos);
             }
         }
+        // the observer only sees the population once all costs are known.
+        let num_evaluations = (self.num_cost_evaluations, self.num_failed_evaluations);
+        let num_known = known.len();
+        for (i, (pos, cost)) in known.into_iter().enumerate() {
+            let idx = size - 1 - i;
+            self.curr[idx].pos = pos;
+            self.set_cost(idx, cost.unwrap());
+        }
+        self.num_cost_evaluations = num_evaluations.0;
+        self.num_failed_evaluations = num_evaluations.1;
+        self.pop_countdown = size - num_known;
+        if 0 == self.pop_countdown {
+            self.notify_observer();
+        }
+    }
 
-        pop
+    // Tolerance for the equality constraints in the current generation, or
+    // None when unconstrained.
+    fn tolerance(&self) -> Option<f32> {
+        let handling = self.settings.constraint_handling;
+        let generation = self.generation;
+        self.settings.constraints.as_ref().map(|_| handling.tolerance(generation))
     }
 
     /// Loops through each individual and updates its personal best.
     fn update_best(&mut self) {
+        let tolerance = self.tolerance();
+        let noise_variance = noise::pooled_variance(self.best.iter().map(|ind| &ind.samples));
         for i in 0..self.curr.len() {
-            let curr = &mut self.curr[i];
-            let best = &mut self.best[i];
-
-            // we use <= here, so that the individual moves even if the cost
-            // stays the same.
-            let mut is_swapping = best.cost.is_none();
-            if !is_swapping {
-                if let Some(ref c) = curr.cost {
-                    if let Some(ref b) = best.cost {
-                        is_swapping = c <= b;
-                    }
+            self.update_parent(i, tolerance, noise_variance);
+        }
+
+        self.memory.update(&self.settings.adaptation);
+        self.trim_archive();
+    }
+
+    // Replaces best[i] with curr[i] if that is better or equal.
+    fn update_parent(&mut self, i: usize, tolerance: Option<f32>, noise_variance: Option<f64>) {
+        let curr = &mut self.curr[i];
+        let best = &mut self.best[i];
+
+        // we use <= here, so that the individual moves even if the cost
+        // stays the same.
+        let mut is_swapping = best.cost.is_none();
+        let mut is_improving = false;
+        if !is_swapping && curr.cost.is_some() {
+            match compare(&curr.cost,
+                          &curr.constraints,
+                          &best.cost,
+                          &best.constraints,
+                          tolerance) {
+                // with noise, only a significant improvement replaces the parent.
+                Some(Ordering::Less) if self.settings.noise.is_some() &&
+                                        is_valid_cost(&best.cost) &&
+                                        is_feasible(&curr.constraints, tolerance) &&
+                                        is_feasible(&best.constraints, tolerance) => {
+                    let significance = self.settings.noise.as_ref().unwrap().significance;
+                    is_swapping = noise::is_significant(&curr.samples,
+                                                        &best.samples,
+                                                        noise_variance,
+                                                        significance);
+                    is_improving = is_swapping;
                 }
+                Some(Ordering::Less) => {
+                    is_swapping = true;
+                    // only a real improvement counts as success.
+                    is_improving = true;
+                }
+                Some(Ordering::Equal) if self.settings.noise.is_some() => {}
+                Some(Ordering::Equal) => is_swapping = true,
+                _ => {}
             }
+        }
 
-            if is_swapping {
-                // replace individual's best. swap is *much* faster than clone.
-                std::mem::swap(curr, best);
+        if !is_swapping {
+            return;
+        }
+
+        // replace individual's best. swap is *much* faster than clone.
+        std::mem::swap(curr, best);
+
+        if is_improving {
+            // the cost difference is meaningless when feasibility decides,
+            // or when the parent's evaluation has failed.
+            let weight = match self.settings.improvement {
+                Some(improvement) if tolerance.is_none() && is_valid_cost(&curr.cost) => {
+                    improvement(curr.cost.as_ref().unwrap(), best.cost.as_ref().unwrap())
+                }
+                _ => 1.0,
+            };
+            self.memory.record(best.cr, best.f, weight);
+            if self.settings.archive_size > 0 {
+                self.archive.push(curr.pos.clone());
+            }
+        }
+    }
+
+    // Randomly removes archived positions that don't fit any more.
+    fn trim_archive(&mut self) {
+        let capacity = self.settings.archive_size * self.curr.len() / self.settings.pop_size;
+        while self.archive.len() > capacity {
+            let idx = Range::new(0, self.archive.len()).ind_sample(&mut self.settings.rng);
+            self.archive.swap_remove(idx);
+        }
+    }
+
+    // Removes the worst individuals when the population size is linearly
+    // reduced. Has to be called after update_best.
+    fn reduce_population(&mut self) {
+        let new_size = match self.settings.pop_reduction {
+            Some(ref r) => {
+                let initial = self.settings.pop_size as f64;
+                let run_evaluations = self.num_cost_evaluations - self.run_start_evaluations;
+                let progress = run_evaluations.min(r.max_cost_evaluations) as f64 /
+                               r.max_cost_evaluations as f64;
+                let size = initial - (initial - r.final_pop_size as f64) * progress;
+                size.round() as usize
+            }
+            // the first generation is larger with opposition-based initialization.
+            None => self.settings.pop_size,
+        };
+        if new_size >= self.curr.len() {
+            return;
+        }
+
+        // keep the best individuals, in their original order.
+        let ranked = self.ranked_best();
+        let mut is_kept = vec![false; self.best.len()];
+        for &i in &ranked[..new_size] {
+            is_kept[i] = true;
+        }
+        if let Some(bi) = self.best_idx {
+            // with many equal costs, the global best might not be ranked first.
+            if !is_kept[bi] {
+                is_kept[ranked[new_size - 1]] = false;
+                is_kept[bi] = true;
+            }
+        }
+
+        let mut i = 0;
+        self.curr.retain(|_| {
+            i += 1;
+            is_kept[i - 1]
+        });
+        i = 0;
+        self.best.retain(|_| {
+            i += 1;
+            is_kept[i - 1]
+        });
+
+        // the global best's index moves.
+        if let Some(bi) = self.best_idx {
+            self.best_idx = Some(is_kept[..bi].iter().filter(|&&k| k).count());
+        }
+        self.between_popsize = Range::new(0, new_size);
+        self.trim_archive();
+    }
+
+    // Indices of all individuals in best, sorted from best to worst.
+    fn ranked_best(&self) -> Vec<usize> {
+        let tolerance = self.tolerance();
+        let best = &self.best;
+        let mut ranked: Vec<usize> = (0..best.len()).collect();
+        ranked.sort_by(|&a, &b| {
+            compare(&best[a].cost,
+                    &best[a].constraints,
+                    &best[b].cost,
+                    &best[b].constraints,
+                    tolerance)
+                .unwrap_or(Ordering::Equal)
+        });
+        ranked
+    }
+
+    // Searches best for the global best individual. Necessary when the
+    // tolerance of the constraints has changed, which might change the
+    // order of individuals.
+    fn find_global_best(&mut self) {
+        let ranked = self.ranked_best();
+        let bi = ranked[0];
+        if self.best[bi].cost.is_some() {
+            self.best_idx = Some(bi);
+            self.best_cost_cache = self.best[bi].cost.clone();
+            self.best_constraints_cache = self.best[bi].constraints.clone();
+        }
+    }
+
+    // With `Adaptation::Constant`, sets the parameters of all trials of the
+    // next generation, with a newly drawn `f`.
+    fn draw_constant_parameters(&mut self) {
+        if let Adaptation::Constant { cr, f_min_max: (min, max) } = self.settings.adaptation {
+            let f = if min < max {
+                Range::new(min, max).ind_sample(&mut self.settings.rng)
+            } else {
+                min
+            };
+            for ind in &mut self.curr {
+                ind.cr = cr;
+                ind.f = f;
             }
         }
     }
@@ -364,139 +1126,563 @@ impl<F, R, C> Population<F, R, C>
     // for a fast cost function it is important to use a fast random number
     // generator.
     fn update_positions(&mut self) {
+        let len = self.curr.len();
+        self.update_positions_between(0, len);
+    }
+
+    // Creates the trials curr[first..end].
+    fn update_positions_between(&mut self, first: usize, end: usize) {
+        // for strategies that use one of the top individuals, rank them by cost.
+        let mut ranked: Vec<usize> = Vec::new();
+        let mut between_pbest = None;
+        if let Some(p) = self.settings.mutation.pbest_fraction() {
+            ranked = self.ranked_best();
+            let num_pbest = (p * ranked.len() as f32).round() as usize;
+            between_pbest = Some(Range::new(0, num_pbest.max(1).min(ranked.len())));
+        }
+
         let rng = &mut self.settings.rng;
-        for i in 0..self.curr.len() {
-            // sample 3 different individuals
-            let id1 = self.between_popsize.ind_sample(rng);
+        let mutation = &self.settings.mutation;
 
-            let mut id2 = self.between_popsize.ind_sample(rng);
-            while id2 == id1 {
-                id2 = self.between_popsize.ind_sample(rng);
-            }
+        // after update_best, the global best is always in best.
+        let global_best_pos = &self.best[self.best_idx.unwrap()].pos;
+
+        // the last random individual might come from the archive.
+        let between_pop_archive = Range::new(0, self.best.len() + self.archive.len());
 
-            let mut id3 = self.between_popsize.ind_sample(rng);
-            while id3 == id1 || id3 == id2 {
-                id3 = self.between_popsize.ind_sample(rng);
+        let mut ids = vec![0; mutation.num_random()];
+        let mut random = Vec::with_capacity(ids.len());
+        for i in first..end {
+            // sample distinct random individuals
+            for k in 0..ids.len() {
+                let between = if k + 1 == ids.len() {
+                    &between_pop_archive
+                } else {
+                    &self.between_popsize
+                };
+                let mut id = between.ind_sample(rng);
+                while ids[..k].contains(&id) {
+                    id = between.ind_sample(rng);
+                }
+                ids[k] = id;
             }
 
             let curr = &mut self.curr[i];
             let best = &self.best[i];
 
-            // see "Self-Adapting Control Parameters in Differential Evolution:
-            // A Comparative Study on Numerical Benchmark Problems"
-            if rng.gen::<f32>() < self.settings.cr_change_probability {
-                curr.cr = self.between_cr.ind_sample(rng);
-            } else {
-                curr.cr = best.cr;
+            match self.settings.adaptation {
+                Adaptation::Jde => {
+                    // see "Self-Adapting Control Parameters in Differential Evolution:
+                    // A Comparative Study on Numerical Benchmark Problems"
+                    if rng.gen::<f32>() < self.settings.cr_change_probability {
+                        curr.cr = self.between_cr.ind_sample(rng);
+                    } else {
+                        curr.cr = best.cr;
+                    }
+                    if rng.gen::<f32>() < self.settings.f_change_probability {
+                        curr.f = self.between_f.ind_sample(rng);
+                    } else {
+                        curr.f = best.f;
+                    }
+                }
+                Adaptation::Jade { .. } |
+                Adaptation::Shade { .. } => {
+                    let (cr, f) = self.memory.sample(rng);
+                    curr.cr = cr;
+                    curr.f = f;
+                }
+                // already set for the whole generation.
+                Adaptation::Constant { .. } => {}
             }
-            if rng.gen::<f32>() < self.settings.f_change_probability {
-                curr.f = self.between_f.ind_sample(rng);
+
+            let pbest_pos = match between_pbest {
+                Some(ref between) => &self.best[ranked[between.ind_sample(rng)]].pos,
+                None => global_best_pos,
+            };
+
+            random.clear();
+            let pop_best = &self.best;
+            let archive = &self.archive;
+            random.extend(ids.iter().map(|&id| if id < pop_best.len() {
+                &pop_best[id].pos[..]
             } else {
-                curr.f = best.f;
-            }
+                &archive[id - pop_best.len()][..]
+            }));
 
-            let curr_pos = &mut curr.pos;
-            let best_pos = &best.pos;
-            let best1_pos = &self.best[id1].pos;
-            let best2_pos = &self.best[id2].pos;
-            let best3_pos = &self.best[id3].pos;
+            let donors = Donors {
+                target: &best.pos,
+                best: global_best_pos,
+                pbest: pbest_pos,
+                random: &random,
+            };
+            mutation.mutate(&donors, curr.f, &mut curr.pos);
 
-            let forced_mutation_dim = self.between_dim.ind_sample(rng);
+            self.settings.crossover.crossover(rng, curr.cr, &best.pos, &mut curr.pos);
 
-            // This implements the DE/rand/1/bin, the most widely used algorithm.
-            // See "A Comparative Study of Differential Evolution Variants for
-            // Global Optimization (2006)".
-            for d in 0..self.dim {
-                if d == forced_mutation_dim || rng.gen::<f32>() < curr.cr {
-                    curr_pos[d] = best3_pos[d] + curr.f * (best1_pos[d] - best2_pos[d]);
-                } else {
-                    curr_pos[d] = best_pos[d];
+            if let Some(ref bounds) = self.settings.bounds {
+                let handling = self.settings.boundary_handling;
+                for (d, &min_max) in bounds.iter().enumerate() {
+                    curr.pos[d] = handling.repair(rng, min_max, best.pos[d], curr.pos[d]);
                 }
             }
+            if let Some(ref space) = self.settings.search_space {
+                // categories come from a random member of the population, or
+                // from the target when the strategy has none.
+                let donor = ids.iter()
+                    .position(|&id| id < pop_best.len())
+                    .map_or(&best.pos[..], |k| random[k]);
+                space.repair(rng, &best.pos, donor, &mut curr.pos);
+            }
 
             // reset cost, has to be updated by the user.
             curr.cost = None;
+            curr.constraints.clear();
         }
     }
 
 
-    /// Gets a tuple of the best cost and best position found so far.
-    pub fn best(&self) -> Option<(&C, &[f32])> {
+    /// Gets a tuple of the best cost and best position found so far. With
+    /// noise handling, the cost is the mean of all evaluations of the
+    /// position, see `best_num_samples()`.
+    pub fn best(&self) -> Option<(&C, &[T])> {
+        self.best_individual().map(|ind| (ind.cost.as_ref().unwrap(), &ind.pos[..]))
+    }
+
+    /// Gets the number of evaluations that the cost of `best()` is averaged
+    /// over. This is 1 without noise handling.
+    pub fn best_num_samples(&self) -> Option<usize> {
+        let is_noisy = self.settings.noise.is_some();
+        self.best_individual().map(|ind| if is_noisy { ind.samples.n.max(1) } else { 1 })
+    }
+
+    /// Like `best()`, but decodes the position into the typed values of
+    /// the `search_space`. None without a search space.
+    pub fn best_values(&self) -> Option<(&C, Vec<Value>)> {
+        let space = self.settings.search_space.as_ref()?;
+        self.best().map(|(cost, pos)| (cost, space.decode(pos)))
+    }
+
+    fn best_individual(&self) -> Option<&Individual<C, T>> {
         if let Some(bi) = self.best_idx {
             let curr = &self.curr[bi];
             let best = &self.best[bi];
 
             if curr.cost.is_none() {
-                return Some((best.cost.as_ref().unwrap(), &best.pos));
+                return Some(best);
             }
             if best.cost.is_none() {
-                return Some((curr.cost.as_ref().unwrap(), &curr.pos));
+                return Some(curr);
             }
-            if curr.cost.as_ref().unwrap() < best.cost.as_ref().unwrap() {
-                return Some((curr.cost.as_ref().unwrap(), &curr.pos));
+            // with immediate updating, an evaluated trial that is still in
+            // curr has been rejected.
+            if self.settings.updating == Updating::Immediate {
+                return Some(best);
             }
-            return Some((best.cost.as_ref().unwrap(), &best.pos));
+            if compare(&curr.cost,
+                       &curr.constraints,
+                       &best.cost,
+                       &best.constraints,
+                       self.tolerance()) == Some(Ordering::Less) {
+                return Some(curr);
+            }
+            Some(best)
         } else {
             None
         }
     }
 
+    /// Gets the total constraint violation of the best solution found so
+    /// far. This is 0 when the solution is feasible, or when there are no
+    /// constraints.
+    pub fn best_violation(&self) -> Option<f64> {
+        let tolerance = match self.tolerance() {
+            Some(tolerance) => tolerance,
+            None => return self.best_idx.map(|_| 0.0),
+        };
+        self.best_idx.map(|_| total_violation(&self.best_constraints_cache, tolerance))
+    }
+
     /// Gets the total number of times the cost function has been evaluated.
+    /// Failed evaluations are not included.
     pub fn num_cost_evaluations(&self) -> usize {
         self.num_cost_evaluations
     }
 
+    /// Gets the number of failed evaluations, including the retried ones.
+    pub fn num_failed_evaluations(&self) -> usize {
+        self.num_failed_evaluations
+    }
+
     /// Performs a single cost evaluation, and updates best positions and
     /// evolves the population if the whole population has been evaluated.
     /// Returns the cost value of the current best solution found.
     pub fn eval(&mut self) {
+        self.next_generation_if_evaluated();
+
+        // perform a single fitness evaluation
+        self.pop_countdown -= 1;
+        let idx = self.pop_countdown;
+        let is_immediate = self.settings.updating == Updating::Immediate;
+        if is_immediate && self.generation > 0 {
+            self.update_positions_between(idx, idx + 1);
+        }
+        let cost = self.evaluate(idx);
+        self.set_cost(idx, cost);
+        if is_immediate {
+            let noise_variance = noise::pooled_variance(self.best.iter().map(|ind| &ind.samples));
+            let tolerance = self.tolerance();
+            self.update_parent(idx, tolerance, noise_variance);
+            if self.settings.noise.is_some() && self.best_idx == Some(idx) {
+                // a lucky trial might have been rejected as not significant.
+                self.find_global_best();
+            }
+        }
+    }
+
+    /// Returns the indices and positions of all individuals that still
+    /// need a cost, for evaluating them outside of the population, e.g. on
+    /// a batch scheduler. If all costs of the current generation have been
+    /// told, the population is evolved first. The cost function of the
+    /// settings is not used with `ask()` and `tell()`.
+    ///
+    /// Don't mix `ask()` and `tell()` with `eval()` within a generation.
+    /// Noise handling and memetic local search evaluate with the cost
+    /// function, so they are not supported.
+    pub fn ask(&mut self) -> Vec<(usize, &[T])> {
+        assert!(self.settings.noise.is_none(),
+                "ask() does not support noise handling");
+        assert!(self.settings.memetic.is_none(),
+                "ask() does not support memetic local search");
+        assert!(self.settings.updating == Updating::Deferred,
+                "ask() does not support immediate updating");
+        self.next_generation_if_evaluated();
+        self.curr
+            .iter()
+            .enumerate()
+            .filter(|&(_, c)| c.cost.is_none())
+            .map(|(i, c)| (i, &c.pos[..]))
+            .collect()
+    }
+
+    /// Sets the cost of the individual with the given index, as returned by
+    /// `ask()`. Costs can be told in any order. Once the costs of all
+    /// individuals are told, the next call to `ask()` evolves the
+    /// population.
+    pub fn tell(&mut self, index: usize, cost: C) {
+        assert!(self.settings.noise.is_none(),
+                "tell() does not support noise handling");
+        assert!(self.settings.memetic.is_none(),
+                "tell() does not support memetic local search");
+        assert!(index < self.curr.len() && self.curr[index].cost.is_none(),
+                "index was not returned by ask(), or its cost has already been told");
+        self.pop_countdown -= 1;
+        self.set_cost(index, cost);
+    }
+
+    // if the whole pop has been evaluated, evolve it to update positions.
+    // this also copies curr to best, if better.
+    fn next_generation_if_evaluated(&mut self) {
         if 0 == self.pop_countdown {
-            // if the whole pop has been evaluated, evolve it to update positions.
-            // this also copies curr to best, if better.
-            self.update_best();
-            self.update_positions();
+            match self.settings.updating {
+                Updating::Deferred => self.update_best(),
+                Updating::Immediate => {
+                    // only initial candidates with known costs are left.
+                    let tolerance = self.tolerance();
+                    for i in 0..self.curr.len() {
+                        if self.best[i].cost.is_none() {
+                            self.update_parent(i, tolerance, None);
+                        }
+                    }
+                    self.memory.update(&self.settings.adaptation);
+                    self.trim_archive();
+                }
+            }
+            if self.settings.noise.is_some() {
+                self.reevaluate_survivors();
+            }
+            if let Some(memetic) = self.settings.memetic {
+                if (self.generation + 1) % memetic.generations == 0 {
+                    self.polish(memetic.method, memetic.max_evaluations);
+                }
+            }
+            self.reduce_population();
+            self.generation += 1;
+            if let (Some(_), ConstraintHandling::Epsilon { .. }) =
+                   (self.settings.constraints.as_ref(), self.settings.constraint_handling) {
+                self.find_global_best();
+            }
+            self.draw_constant_parameters();
+            if self.settings.updating == Updating::Deferred {
+                self.update_positions();
+            }
             self.pop_countdown = self.curr.len();
         }
+    }
+
+    // Evaluates the survivors once more until they have enough samples, and
+    // updates their mean costs and the global best.
+    fn reevaluate_survivors(&mut self) {
+        let noise = self.settings.noise.unwrap();
+        for i in 0..self.best.len() {
+            if self.best[i].cost.is_none() || self.best[i].samples.n >= noise.max_samples {
+                continue;
+            }
+            let (cost, num_retries) = fallible::evaluate(&self.settings.cost_function,
+                                                         &self.best[i].pos,
+                                                         self.settings.max_retries);
+            self.num_failed_evaluations += num_retries;
+            self.count_evaluation(&cost);
+            if is_valid_cost(&cost) {
+                let ind = &mut self.best[i];
+                ind.samples.add((noise.to_f64)(&cost));
+                ind.cost = Some((noise.from_f64)(ind.samples.mean));
+            }
+        }
+        self.find_global_best();
+    }
 
-        // perform a single fitness evaluation
-        self.pop_countdown -= 1;
-        let curr = &mut self.curr[self.pop_countdown];
+    // Evaluates the cost of curr[idx], with retries of failed evaluations.
+    fn evaluate(&mut self, idx: usize) -> C {
+        let (cost, num_retries) = fallible::evaluate(&self.settings.cost_function,
+                                                     &self.curr[idx].pos,
+                                                     self.settings.max_retries);
+        self.num_failed_evaluations += num_retries;
+        cost
+    }
 
-        let cost = (self.settings.cost_function)(&curr.pos);
+    // Counts a cost either as evaluation or as failed evaluation.
+    fn count_evaluation(&mut self, cost: &C) {
+        if is_valid_cost(cost) {
+            self.num_cost_evaluations += 1;
+        } else {
+            self.num_failed_evaluations += 1;
+        }
+    }
+
+    // Stores the cost of curr[idx], evaluates its constraints, and updates
+    // the global best.
+    fn set_cost(&mut self, idx: usize, cost: C) {
+        self.count_evaluation(&cost);
+        let tolerance = self.tolerance();
+        let curr = &mut self.curr[idx];
+        curr.samples = Samples::first(&cost, &self.settings.noise);
         curr.cost = Some(cost);
-        self.num_cost_evaluations += 1;
+        if let Some(ref constraints) = self.settings.constraints {
+            curr.constraints = constraints(&curr.pos);
+        }
 
         // see if we have improved the global best
         if self.best_cost_cache.is_none() ||
-           curr.cost.as_ref().unwrap() < self.best_cost_cache.as_ref().unwrap() {
+           compare(&curr.cost,
+                   &curr.constraints,
+                   &self.best_cost_cache,
+                   &self.best_constraints_cache,
+                   tolerance) == Some(Ordering::Less) {
             self.best_cost_cache = curr.cost.clone();
-            self.best_idx = Some(self.pop_countdown);
+            self.best_constraints_cache = curr.constraints.clone();
+            self.best_idx = Some(idx);
+        }
+
+        if 0 == self.pop_countdown {
+            self.notify_observer();
+        }
+    }
+
+    // Calls the observer, if any, with the status of the whole generation.
+    fn notify_observer(&mut self) {
+        if let Some(mut observer) = self.settings.observer.take() {
+            observer(&self.status());
+            self.settings.observer = Some(observer);
+        }
+    }
+
+    /// Evaluates generation after generation until the criterion fires, and
+    /// returns why it has stopped. See the `termination` module.
+    pub fn run_until<X: Termination<C, T>>(&mut self, mut criterion: X) -> Reason {
+        loop {
+            self.eval();
+            if 0 == self.pop_countdown {
+                if let Some(reason) = criterion.check(&self.status()) {
+                    return reason;
+                }
+            }
+        }
+    }
+
+    // Status after the whole generation has been evaluated.
+    fn status(&self) -> Status<'_, C, T> {
+        let survivors = self.survivors();
+        Status {
+            num_generations: self.generation + 1,
+            num_cost_evaluations: self.num_cost_evaluations,
+            num_failed_evaluations: self.num_failed_evaluations,
+            best_cost: self.best_cost_cache.as_ref(),
+            costs: survivors.iter().map(|ind| ind.cost.as_ref().unwrap()).collect(),
+            positions: survivors.iter().map(|ind| &ind.pos[..]).collect(),
+            cr: survivors.iter().map(|ind| ind.cr).collect(),
+            f: survivors.iter().map(|ind| ind.f).collect(),
+        }
+    }
+
+    // For each index the individual that will survive update_best, either
+    // the evaluated trial or its parent.
+    fn survivors(&self) -> Vec<&Individual<C, T>> {
+        let tolerance = self.tolerance();
+        self.curr
+            .iter()
+            .zip(self.best.iter())
+            .map(|(curr, best)| {
+                if best.cost.is_none() {
+                    return curr;
+                }
+                if curr.cost.is_none() {
+                    return best;
+                }
+                match compare(&curr.cost, &curr.constraints, &best.cost, &best.constraints, tolerance) {
+                    Some(Ordering::Less) | Some(Ordering::Equal) => curr,
+                    _ => best,
+                }
+            })
+            .collect()
+    }
+
+    /// Replaces the worst individual with a candidate, e.g. a good position
+    /// found by other means. Without a known cost, the candidate is
+    /// evaluated immediately. It keeps the control parameters of the
+    /// replaced individual, and never replaces the global best.
+    ///
+    /// The candidate has to be within the `bounds`. Integer and categorical
+    /// variables of the `search_space` are rounded.
+    pub fn inject(&mut self, mut pos: Vec<T>, cost: Option<C>) {
+        self.prepare_candidate(&mut pos);
+        let cost = match cost {
+            Some(cost) => cost,
+            None => {
+                let (cost, num_retries) = fallible::evaluate(&self.settings.cost_function,
+                                                             &pos,
+                                                             self.settings.max_retries);
+                self.num_failed_evaluations += num_retries;
+                self.count_evaluation(&cost);
+                cost
+            }
+        };
+
+        let idx = self.worst_idx();
+        let tolerance = self.tolerance();
+        let ind = &mut self.best[idx];
+        ind.constraints = match self.settings.constraints {
+            Some(ref constraints) => constraints(&pos),
+            None => Vec::new(),
+        };
+        ind.pos = pos;
+        ind.samples = Samples::first(&cost, &self.settings.noise);
+        ind.cost = Some(cost);
+
+        if self.best_cost_cache.is_none() ||
+           compare(&ind.cost,
+                   &ind.constraints,
+                   &self.best_cost_cache,
+                   &self.best_constraints_cache,
+                   tolerance) == Some(Ordering::Less) {
+            self.best_cost_cache = ind.cost.clone();
+            self.best_constraints_cache = ind.constraints.clone();
+            self.best_idx = Some(idx);
         }
     }
 
+    // Checks that a candidate fits the dimension and the bounds, and rounds
+    // it to the search space.
+    fn prepare_candidate(&self, pos: &mut [T]) {
+        assert!(pos.len() == self.settings.min_max_pos.len(),
+                "candidate needs to have the same dimension as min_max_pos");
+        if let Some(ref bounds) = self.settings.bounds {
+            assert!(pos.iter().zip(bounds.iter()).all(|(x, b)| b.0 <= *x && *x <= b.1),
+                    "candidate has to be within bounds");
+        }
+        if let Some(ref space) = self.settings.search_space {
+            space.round(pos);
+        }
+    }
+
+    // Index of the worst individual in best, other than the one that holds
+    // the global best. Unevaluated individuals are the worst.
+    fn worst_idx(&self) -> usize {
+        if let Some(idx) = self.best.iter().position(|ind| ind.cost.is_none()) {
+            return idx;
+        }
+        // the global best may be the trial in curr, then its parent can go.
+        let global_best = self.best_individual();
+        self.ranked_best()
+            .into_iter()
+            .rev()
+            .find(|&i| global_best.map_or(true, |ind| !std::ptr::eq(ind, &self.best[i])))
+            .unwrap_or(0)
+    }
 
     /// Gets an iterator for this population. Each call to `next()`
     /// performs one cost evaluation.
-    pub fn iter(&mut self) -> PopIter<F, R, C> {
+    pub fn iter(&mut self) -> PopIter<'_, F, R, C, T> {
         PopIter { pop: self }
     }
 }
 
 
+#[cfg(feature = "parallel")]
+impl<F, R, C, T> Population<F, R, C, T>
+    where F: Fn(&[T]) -> C + Sync,
+          R: rand::Rng,
+          C: PartialOrd + Clone + Send,
+          T: Float
+{
+    /// Evaluates all individuals of the current generation that have not
+    /// been evaluated yet concurrently with rayon, and evolves the
+    /// population first if the whole population has already been
+    /// evaluated. Returns the number of cost evaluations performed.
+    ///
+    /// The global best is updated in the same order as with `eval()`, so
+    /// the optimization gives exactly the same results as the serial
+    /// version, independent of the number of threads. Constraints are
+    /// evaluated on the calling thread.
+    ///
+    /// Only available with the `parallel` feature.
+    pub fn eval_generation(&mut self) -> usize {
+        assert!(self.settings.updating == Updating::Deferred,
+                "eval_generation() does not support immediate updating");
+        self.next_generation_if_evaluated();
+
+        let remaining = self.pop_countdown;
+        let costs: Vec<(C, usize)> = {
+            let cost_function = &self.settings.cost_function;
+            let max_retries = self.settings.max_retries;
+            let positions: Vec<&[T]> = self.curr[..remaining].iter().map(|c| &c.pos[..]).collect();
+            positions.par_iter().map(|pos| fallible::evaluate(cost_function, pos, max_retries)).collect()
+        };
+
+        for (idx, (cost, num_retries)) in costs.into_iter().enumerate().rev() {
+            self.pop_countdown -= 1;
+            self.num_failed_evaluations += num_retries;
+            self.set_cost(idx, cost);
+        }
+        remaining
+    }
+}
+
 /// Iterator for the differential evolution, to perform a single cost
 /// evaluation every time `move()` is called.
-pub struct PopIter<'a, F, R, C>
-    where F: 'a + Fn(&[f32]) -> C,
+pub struct PopIter<'a, F, R, C, T = f32>
+    where F: 'a + Fn(&[T]) -> C,
           R: 'a + rand::Rng,
-          C: 'a + PartialOrd + Clone
+          C: 'a + PartialOrd + Clone,
+          T: 'a + Float
 {
-    pop: &'a mut Population<F, R, C>,
+    pop: &'a mut Population<F, R, C, T>,
 }
 
-impl<'a, F, R, C> Iterator for PopIter<'a, F, R, C>
-    where F: 'a + Fn(&[f32]) -> C,
+impl<'a, F, R, C, T> Iterator for PopIter<'a, F, R, C, T>
+    where F: 'a + Fn(&[T]) -> C,
           R: 'a + rand::Rng,
-          C: PartialOrd + Clone
+          C: PartialOrd + Clone,
+          T: 'a + Float
 {
     type Item = C;
 
@@ -509,5 +1695,815 @@ impl<'a, F, R, C> Iterator for PopIter<'a, F, R, C>
 
 #[cfg(test)]
 mod tests {
-    // TODO
+    #[cfg(feature = "serde")]
+    extern crate serde_json;
+
+    use super::*;
+    use mutation::*;
+    use crossover::*;
+
+    fn sum_of_squares(pos: &[f32]) -> f32 {
+        pos.iter().fold(0.0, |sum, x| sum + x * x)
+    }
+
+    fn assert_send<T: Send>() {}
+
+    #[test]
+    fn population_is_send() {
+        assert_send::<Settings<fn(&[f32]) -> f32, rand::XorShiftRng, f32>>();
+        assert_send::<Population<fn(&[f32]) -> f32, rand::XorShiftRng, f32>>();
+        assert_send::<Population<fn(&[f64]) -> f64, XorShift128, f64, f64>>();
+    }
+
+    #[test]
+    fn all_mutation_strategies_converge() {
+        let strategies: Vec<Box<dyn MutationStrategy>> = vec![Box::new(Rand1),
+                                                              Box::new(Best1),
+                                                              Box::new(Rand2),
+                                                              Box::new(Best2),
+                                                              Box::new(CurrentToBest1),
+                                                              Box::new(RandToBest1),
+                                                              Box::new(CurrentToPBest1 { p: 0.1 })];
+        for mutation in strategies {
+            let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares);
+            s.pop_size = 20;
+            s.mutation = mutation;
+            let mut de = Population::new(s);
+            de.iter().nth(5000);
+            assert!(*de.best().unwrap().0 < 0.01);
+        }
+    }
+
+    #[test]
+    fn smallest_population_for_the_mutation_strategy() {
+        let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares);
+        s.pop_size = 3;
+        let mut de = Population::new(s);
+        de.iter().nth(100);
+        assert_eq!(3, de.individuals().len());
+    }
+
+    #[test]
+    fn jade_converges() {
+        let mut de = jade(vec![(-10.0, 10.0); 10], sum_of_squares);
+        de.iter().nth(30000);
+        assert!(*de.best().unwrap().0 < 1e-6);
+    }
+
+    #[test]
+    fn l_shade_converges() {
+        let mut de = l_shade(vec![(-10.0, 10.0); 10], sum_of_squares, 50000);
+        de.iter().nth(50000);
+        assert!(*de.best().unwrap().0 < 1e-6);
+    }
+
+    #[test]
+    fn l_shade_survives_infinite_costs() {
+        // infinite improvements used to make the memory NaN, and sampling f hang.
+        let cost = |pos: &[f32]| if pos[0] > 0.0 {
+            f32::INFINITY
+        } else {
+            sum_of_squares(pos)
+        };
+        let mut de = Population::new(Settings::l_shade(vec![(-10.0, 10.0); 5], cost, 20000)
+            .with_seed(1));
+        de.iter().nth(20000);
+        assert!(*de.best().unwrap().0 < 1e-3);
+        assert!(de.trials().iter().all(|ind| ind.f() > 0.0 && ind.f() <= 1.0));
+    }
+
+    #[test]
+    fn infinite_improvements_are_capped() {
+        let shade = Adaptation::Shade { memory_size: 1 };
+        let mut memory = adaptation::SuccessMemory::new(&shade);
+        memory.record(0.9, 0.9, f64::INFINITY);
+        memory.record(0.1, 0.1, 1.0);
+        memory.update(&shade);
+
+        // both are weighted equally, so the mean cr moves to about 0.82.
+        let mut rng = XorShift128::seed_from_u64(1);
+        let mean_cr = (0..1000).fold(0.0, |sum, _| sum + memory.sample(&mut rng).0) / 1000.0;
+        assert!(mean_cr > 0.7 && mean_cr < 0.9);
+    }
+
+    #[test]
+    fn bounds_are_never_violated() {
+        let handlings = vec![BoundaryHandling::Clamp,
+                             BoundaryHandling::Reflect,
+                             BoundaryHandling::Wrap,
+                             BoundaryHandling::Random,
+                             BoundaryHandling::Midpoint];
+        for handling in handlings {
+            // the optimum is outside of the bounds.
+            let mut s = Settings::default(vec![(1.0, 2.0); 3], |pos: &[f32]| {
+                assert!(pos.iter().all(|x| (1.0..=10.0).contains(x)));
+                pos.iter().fold(0.0, |sum, x| sum + (x + 5.0) * (x + 5.0))
+            });
+            s.pop_size = 20;
+            s.bounds = Some(vec![(1.0, 10.0); 3]);
+            s.boundary_handling = handling;
+            let mut de = Population::new(s);
+            de.iter().nth(2000);
+        }
+    }
+
+    #[test]
+    fn inequality_constraint_is_satisfied() {
+        // minimize x + y within the unit circle.
+        let mut s = Settings::default(vec![(-2.0, 2.0); 2], |pos: &[f32]| pos[0] + pos[1]);
+        s.pop_size = 30;
+        s.constraints = Some(Box::new(|pos: &[f32]| {
+            vec![Constraint::Inequality(pos[0] * pos[0] + pos[1] * pos[1] - 1.0)]
+        }));
+        let mut de = Population::new(s);
+        de.iter().nth(10000);
+        assert_eq!(Some(0.0), de.best_violation());
+        assert!((*de.best().unwrap().0 + 2.0f32.sqrt()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn epsilon_constrained_equality() {
+        // minimize x^2 + y^2 on the line x + y = 1.
+        let mut s = Settings::default(vec![(-2.0, 2.0); 2], sum_of_squares);
+        s.pop_size = 30;
+        s.constraints = Some(Box::new(|pos: &[f32]| vec![Constraint::Equality(pos[0] + pos[1] - 1.0)]));
+        s.constraint_handling = ConstraintHandling::Epsilon {
+            initial_tolerance: 1.0,
+            final_tolerance: 1e-4,
+            generations: 200,
+        };
+        let mut de = Population::new(s);
+        de.iter().nth(20000);
+        assert_eq!(Some(0.0), de.best_violation());
+        assert!((*de.best().unwrap().0 - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn gde3_finds_pareto_front() {
+        // Schaffer's problem: the front is x in [0, 2], with f2 = (sqrt(f1) - 2)^2.
+        let cost = |pos: &[f32]| vec![pos[0] * pos[0], (pos[0] - 2.0) * (pos[0] - 2.0)];
+        let mut s = Settings::default(vec![(-10.0, 10.0)], cost);
+        s.rng = rand::SeedableRng::from_seed([1, 2, 3, 4]);
+        let mut de = Gde3::new(s);
+        de.iter().nth(10000);
+        let front = de.front();
+        assert_eq!(100, front.len());
+        for (cost, pos) in front {
+            assert!(pos[0] > -0.01 && pos[0] < 2.01);
+            assert!((cost[1] - (cost[0].sqrt() - 2.0).powi(2)).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn gde3_survives_only_failed_evaluations() {
+        let cost = |_: &[f32]| vec![f32::NAN, f32::NAN];
+        let mut s = Settings::default(vec![(-10.0, 10.0)], cost);
+        s.mutation = Box::new(Best1);
+        let mut de = Gde3::new(s);
+        de.iter().nth(1000);
+        assert!(de.front().is_empty());
+        assert_eq!(1001, de.num_failed_evaluations());
+    }
+
+    #[test]
+    fn f64_is_more_precise_than_f32() {
+        let mut de = self_adaptive_de(vec![(-1.0, 1.0); 3], |pos: &[f64]| {
+            pos.iter().fold(0.0, |sum, x| sum + (x - 0.1) * (x - 0.1))
+        });
+        de.iter().nth(30000);
+        assert!(*de.best().unwrap().0 < 1e-20);
+    }
+
+    #[test]
+    fn all_crossovers_converge() {
+        let crossovers: Vec<Box<dyn Crossover>> = vec![Box::new(Binomial),
+                                                       Box::new(Exponential),
+                                                       Box::new(Arithmetic)];
+        for crossover in crossovers {
+            let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares).with_seed(2);
+            s.pop_size = 20;
+            s.crossover = crossover;
+            let mut de = Population::new(s);
+            de.iter().nth(5000);
+            assert!(*de.best().unwrap().0 < 0.01);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn eval_generation_matches_serial_eval() {
+        use rand::{SeedableRng, XorShiftRng};
+        let create = || {
+            let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares);
+            s.pop_size = 20;
+            s.rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+            Population::new(s)
+        };
+        let mut serial = create();
+        let mut parallel = create();
+        for _ in 0..50 {
+            let n = parallel.eval_generation();
+            for _ in 0..n {
+                serial.eval();
+            }
+        }
+        assert_eq!(serial.num_cost_evaluations(), parallel.num_cost_evaluations());
+        assert_eq!(serial.best(), parallel.best());
+    }
+
+    #[test]
+    fn ask_tell_in_any_order() {
+        let mut s = Settings::default(vec![(-10.0, 10.0); 3], |_: &[f32]| -> f32 {
+            panic!("cost function must not be called")
+        });
+        s.pop_size = 20;
+        let mut de = Population::new(s);
+        for _ in 0..250 {
+            let costs: Vec<(usize, f32)> = de.ask()
+                .iter()
+                .map(|&(i, pos)| (i, sum_of_squares(pos)))
+                .collect();
+            assert_eq!(20, costs.len());
+            let (odd, even): (Vec<_>, Vec<_>) = costs.into_iter().partition(|&(i, _)| i % 2 == 1);
+            for (i, cost) in odd.into_iter().chain(even) {
+                de.tell(i, cost);
+            }
+        }
+        assert_eq!(250 * 20, de.num_cost_evaluations());
+        assert!(*de.best().unwrap().0 < 0.01);
+    }
+
+    #[test]
+    fn nan_costs_are_worst() {
+        let mut s = Settings::default(vec![(-10.0, 10.0); 3], |pos: &[f32]| {
+            if pos[0] < 0.5 { f32::NAN } else { sum_of_squares(pos) }
+        });
+        s.pop_size = 20;
+        let mut de = Population::new(s);
+        de.iter().nth(5000);
+        assert_eq!(5001, de.num_cost_evaluations() + de.num_failed_evaluations());
+        assert!(de.num_failed_evaluations() > 0);
+        let cost = *de.best().unwrap().0;
+        assert!((0.25..0.26).contains(&cost));
+    }
+
+    #[test]
+    fn failed_evaluations_are_retried() {
+        use std::cell::Cell;
+        let num_calls = Cell::new(0);
+        let mut s = Settings::default(vec![(-10.0, 10.0); 3], |pos: &[f32]| {
+            // every second call fails.
+            num_calls.set(num_calls.get() + 1);
+            if num_calls.get() % 2 == 0 {
+                Err("transient failure")
+            } else {
+                Ok(sum_of_squares(pos))
+            }
+            .into()
+        });
+        s.pop_size = 20;
+        s.max_retries = 1;
+        let mut de = Population::new(s);
+        de.iter().nth(5000);
+        assert_eq!(5001, de.num_cost_evaluations());
+        assert_eq!(5000, de.num_failed_evaluations());
+        match *de.best().unwrap().0 {
+            Fallible::Cost(cost) => assert!(cost < 0.01),
+            Fallible::Failed => panic!("best evaluation has failed"),
+        }
+    }
+
+    #[test]
+    fn run_until_reports_the_criterion() {
+        use termination::*;
+        let create = || {
+            let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares);
+            s.pop_size = 20;
+            Population::new(s)
+        };
+
+        let mut de = create();
+        assert_eq!(Reason::MaxGenerations, de.run_until(MaxGenerations(10)));
+        assert_eq!(200, de.num_cost_evaluations());
+
+        let mut de = create();
+        let reason = de.run_until(Any(vec![Box::new(MaxEvaluations(100000)),
+                                           Box::new(TargetCost(1e-3))]));
+        assert_eq!(Reason::TargetCost, reason);
+        assert!(*de.best().unwrap().0 <= 1e-3);
+
+        let mut de = create();
+        let reason = de.run_until(All(vec![Box::new(PositionSpread(1e-3)),
+                                           Box::new(CostSpread { tol: 0.01, atol: 1e-8 })]));
+        assert_eq!(Reason::All(vec![Reason::Converged, Reason::Converged]), reason);
+
+        let mut de = create();
+        let reason = de.run_until(Any(vec![Box::new(Stagnation::new(20)),
+                                           Box::new(MaxEvaluations(1000000))]));
+        assert_eq!(Reason::Stagnation, reason);
+
+        // failed evaluations count too, or this would never stop.
+        let mut s = Settings::default(vec![(-10.0, 10.0); 3], |_: &[f32]| f32::NAN);
+        s.pop_size = 20;
+        let mut de = Population::new(s);
+        assert_eq!(Reason::MaxEvaluations, de.run_until(MaxEvaluations(1000)));
+        assert_eq!((0, 1000), (de.num_cost_evaluations(), de.num_failed_evaluations()));
+    }
+
+    #[test]
+    fn observer_is_called_after_each_generation() {
+        use std::sync::{Arc, Mutex};
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares);
+        s.pop_size = 20;
+        let observer_log = log.clone();
+        s.observer = Some(Box::new(move |status: &Status<f32>| {
+            let costs = status.cost_statistics();
+            assert!(costs.min <= costs.median && costs.median <= costs.max);
+            assert_eq!(Some(&(costs.min as f32)), status.best_cost());
+            assert!(status.cr_statistics().max <= 1.0);
+            observer_log.lock().unwrap().push((status.num_generations(),
+                                               status.num_cost_evaluations(),
+                                               status.diversity()));
+        }));
+        let mut de = Population::new(s);
+        de.iter().nth(999);
+
+        let log = log.lock().unwrap();
+        assert_eq!(50, log.len());
+        assert_eq!((50, 1000), (log[49].0, log[49].1));
+        assert!(log[49].2 < log[0].2);
+    }
+
+    #[test]
+    fn resumed_run_is_identical() {
+        use rand::SeedableRng;
+        let settings = || {
+            let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares)
+                .with_rng(XorShift128::from_seed([1, 2, 3, 4]));
+            s.pop_size = 20;
+            s
+        };
+
+        let mut uninterrupted = Population::new(settings());
+        uninterrupted.iter().nth(2000);
+
+        // interrupt in the middle of a generation.
+        let mut de = Population::new(settings());
+        de.iter().nth(1234);
+        let checkpoint = de.checkpoint();
+        #[cfg(feature = "serde")]
+        let checkpoint = {
+            let json = serde_json::to_string(&checkpoint).unwrap();
+            serde_json::from_str(&json).unwrap()
+        };
+        let mut resumed = Population::resume(settings(), checkpoint);
+        resumed.iter().nth(2000 - 1235);
+
+        assert_eq!(uninterrupted.num_cost_evaluations(), resumed.num_cost_evaluations());
+        assert_eq!(uninterrupted.best(), resumed.best());
+    }
+
+    #[test]
+    fn seeded_runs_are_reproducible() {
+        let run = |seed: u64| {
+            let mut s = Settings::jade(vec![(-10.0, 10.0); 3], sum_of_squares).with_seed(seed);
+            s.pop_size = 20;
+            let mut de = Population::new(s);
+            let trajectory: Vec<f32> = de.iter().take(2000).collect();
+            let (cost, pos) = de.best().unwrap();
+            (trajectory, *cost, pos.to_vec())
+        };
+        assert_eq!(run(7), run(7));
+        assert!(run(7) != run(8));
+    }
+
+    #[test]
+    fn seeded_rng_is_stable() {
+        use rand::Rng;
+        let mut rng = XorShift128::seed_from_u64(42);
+        let numbers: Vec<u32> = (0..4).map(|_| rng.next_u32()).collect();
+        assert_eq!(numbers, [1543815037, 1481044185, 3710778427, 2324458198]);
+    }
+
+    #[test]
+    fn space_filling_initializations_are_stratified() {
+        // one point in each of the 16 slices of every dimension.
+        for init in &[Initialization::LatinHypercube, Initialization::Sobol] {
+            let positions = init.positions(&mut rand::weak_rng(), &[(0.0f64, 1.0); 21], 16);
+            for d in 0..21 {
+                let mut slices: Vec<usize> = positions.iter().map(|p| (p[d] * 16.0) as usize).collect();
+                slices.sort();
+                assert_eq!((0..16).collect::<Vec<usize>>(), slices);
+            }
+        }
+    }
+
+    #[test]
+    fn sobol_falls_back_to_halton_in_many_dimensions() {
+        let min_max_pos = [(0.0f64, 1.0); 40];
+        let mut rng = XorShift128::seed_from_u64(1);
+        let sobol = Initialization::Sobol.positions(&mut rng, &min_max_pos, 16);
+        let mut rng = XorShift128::seed_from_u64(1);
+        let halton = Initialization::Halton.positions(&mut rng, &min_max_pos, 16);
+        assert_eq!(halton, sobol);
+    }
+
+    #[test]
+    fn all_initializations_converge() {
+        for &init in &[Initialization::Uniform,
+                       Initialization::LatinHypercube,
+                       Initialization::Halton,
+                       Initialization::Sobol,
+                       Initialization::Opposition] {
+            let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares);
+            s.pop_size = 20;
+            s.initialization = init;
+            let mut de = Population::new(s);
+            for ind in &de.curr {
+                assert!(ind.pos.iter().all(|&x| (-10.0..10.0).contains(&x)));
+            }
+            de.iter().nth(5000);
+            assert_eq!(20, de.curr.len());
+            assert!(*de.best().unwrap().0 < 0.01);
+        }
+    }
+
+    #[test]
+    fn initial_candidates_are_used() {
+        let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares);
+        s.pop_size = 20;
+        s.initial_candidates = vec![(vec![0.5, 0.0, 0.0], Some(0.25)),
+                                    (vec![0.1, 0.0, 0.0], None)];
+        let mut de = Population::new(s);
+
+        // the known candidate is not evaluated again.
+        assert_eq!(Some((&0.25, &[0.5, 0.0, 0.0][..])), de.best());
+        de.iter().nth(18);
+        assert_eq!(19, de.num_cost_evaluations());
+        let (&cost, pos) = de.best().unwrap();
+        assert!((cost - 0.01).abs() < 1e-6);
+        assert_eq!(&[0.1, 0.0, 0.0], pos);
+    }
+
+    #[test]
+    fn observer_waits_for_known_initial_candidates() {
+        use std::sync::{Arc, Mutex};
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares);
+        s.pop_size = 10;
+        s.initial_candidates = (0..10)
+            .map(|i| {
+                let pos = vec![i as f32, 0.0, 0.0];
+                let cost = sum_of_squares(&pos);
+                (pos, Some(cost))
+            })
+            .collect();
+        let observer_log = log.clone();
+        s.observer = Some(Box::new(move |status: &Status<f32>| {
+            observer_log.lock().unwrap().push((status.num_generations(), status.costs().len()));
+        }));
+        let mut de = Population::new(s);
+        assert_eq!(vec![(1, 10)], *log.lock().unwrap());
+        de.iter().nth(9);
+        assert_eq!(vec![(1, 10), (2, 10)], *log.lock().unwrap());
+    }
+
+    #[test]
+    fn injected_candidate_replaces_the_worst() {
+        let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares).with_seed(4);
+        s.pop_size = 20;
+        let mut de = Population::new(s);
+        de.iter().nth(100);
+        let worst = de.ranked_best().pop().unwrap();
+
+        de.inject(vec![0.0; 3], None);
+        assert_eq!(102, de.num_cost_evaluations());
+        assert_eq!(Some((&0.0, &[0.0, 0.0, 0.0][..])), de.best());
+        assert_eq!(Some(0.0), de.best[worst].cost);
+    }
+
+    #[test]
+    fn polish_improves_the_best_within_bounds() {
+        for &method in &[LocalSearch::NelderMead, LocalSearch::Compass] {
+            let mut s = Settings::default(vec![(1.0, 10.0); 3], sum_of_squares).with_seed(5);
+            s.pop_size = 20;
+            s.bounds = Some(vec![(1.0, 10.0); 3]);
+            let mut de = Population::new(s);
+            de.iter().nth(199);
+            let cost = *de.best().unwrap().0;
+
+            assert!(de.polish(method, 1000));
+            assert!(de.num_cost_evaluations() <= 1200);
+            let (polished, pos) = de.best().unwrap();
+            assert!(*polished < cost);
+            assert!(*polished < 3.001, "{:?} polished to {}", method, polished);
+            assert!(pos.iter().all(|&x| (1.0..=10.0).contains(&x)));
+        }
+    }
+
+    #[test]
+    fn memetic_search_counts_its_evaluations() {
+        let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares);
+        s.pop_size = 20;
+        s.memetic = Some(Memetic {
+            method: LocalSearch::Compass,
+            generations: 5,
+            max_evaluations: 10,
+        });
+        let mut de = Population::new(s);
+        // 10 generations, the local search runs after the 5th and 10th.
+        de.iter().nth(200);
+        assert_eq!(201 + 20, de.num_cost_evaluations());
+    }
+
+    #[test]
+    fn noise_handling_averages_lucky_evaluations() {
+        for &(is_handled, updating) in &[(false, Updating::Deferred),
+                                         (true, Updating::Deferred),
+                                         (true, Updating::Immediate)] {
+            // sum of squares with deterministic uniform noise in [-1, 1).
+            let state = std::cell::Cell::new(12345u32);
+            let noisy = move |pos: &[f32]| {
+                state.set(state.get().wrapping_mul(1664525).wrapping_add(1013904223));
+                let noise = (state.get() >> 8) as f32 / (1u32 << 23) as f32 - 1.0;
+                sum_of_squares(pos) + noise
+            };
+            let mut s = Settings::default(vec![(-10.0, 10.0); 3], noisy).with_seed(1);
+            s.pop_size = 20;
+            s.updating = updating;
+            if is_handled {
+                s.noise = Some(NoiseHandling::new(10, 2.0));
+            }
+            let mut de = Population::new(s);
+            de.iter().nth(20000);
+
+            let (cost, pos) = de.best().unwrap();
+            let error = sum_of_squares(pos) - cost;
+            if is_handled {
+                assert_eq!(Some(10), de.best_num_samples());
+                assert!(error < 0.5);
+            } else {
+                assert_eq!(Some(1), de.best_num_samples());
+                assert!(error > 0.9);
+            }
+        }
+    }
+
+    #[test]
+    fn migration_spreads_the_best_individual() {
+        let settings = (0..4)
+            .map(|i| {
+                let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares).with_seed(i);
+                s.pop_size = 20;
+                s
+            })
+            .collect();
+        let migration = Migration {
+            interval: 5,
+            topology: Topology::FullyConnected,
+        };
+        let mut islands = Islands::new(settings, migration);
+        islands.generations().nth(4);
+        assert_eq!(5, islands.num_generations());
+        assert_eq!(400, islands.num_cost_evaluations());
+
+        let best = *islands.best().unwrap().0;
+        assert!(islands.islands().iter().all(|island| *island.best().unwrap().0 == best));
+
+        islands.generations().nth(200);
+        assert!(*islands.best().unwrap().0 < 1e-6);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_islands_match_serial_islands() {
+        let create = || {
+            let settings = (0..3)
+                .map(|i| {
+                    let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares).with_seed(i);
+                    s.pop_size = 20;
+                    if i == 1 {
+                        s.updating = Updating::Immediate;
+                    }
+                    s
+                })
+                .collect();
+            let migration = Migration {
+                interval: 3,
+                topology: Topology::Random,
+            };
+            Islands::new(settings, migration)
+        };
+        let mut serial = create();
+        let mut parallel = create();
+        for _ in 0..30 {
+            serial.eval_generation();
+            parallel.par_eval_generation();
+        }
+        assert_eq!(serial.num_cost_evaluations(), parallel.num_cost_evaluations());
+        assert_eq!(serial.best(), parallel.best());
+    }
+
+    #[test]
+    fn individuals_can_be_inspected() {
+        let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares);
+        s.pop_size = 20;
+        let mut de = Population::new(s);
+        assert_eq!(3, de.dim());
+        assert!(de.individuals().iter().all(|ind| ind.cost().is_none()));
+
+        de.iter().nth(29);
+        assert_eq!(1, de.generation());
+        assert_eq!(20, de.pop_size());
+        assert_eq!(20, de.settings().pop_size);
+
+        // 10 trials of the second generation are evaluated.
+        assert_eq!(10, de.trials().iter().filter(|ind| ind.cost().is_some()).count());
+        let individuals = de.individuals();
+        assert!(individuals.iter().all(|ind| ind.cost().is_some()));
+        assert!(individuals.iter().all(|ind| (0.0..=1.0).contains(&ind.cr())));
+        assert!(individuals.iter().all(|ind| (0.1..=1.0).contains(&ind.f())));
+        let best = individuals.iter().map(|ind| *ind.cost().unwrap()).fold(f32::INFINITY, f32::min);
+        assert_eq!(*de.best().unwrap().0, best);
+    }
+
+    #[test]
+    fn minimize_like_scipy() {
+        let options = MinimizeOptions { seed: Some(2), ..MinimizeOptions::default() };
+        let result = minimize(vec![(-5.0, 5.0); 3], sum_of_squares, options);
+        assert!(result.success, "{}", result.message);
+        assert!(result.fun < 1e-6);
+        assert_eq!(3, result.x.len());
+        assert_eq!(45, result.population.len());
+        assert_eq!(45, result.population_energies.len());
+        assert!(result.population_energies.iter().all(|&e| e >= result.fun));
+        assert!(result.nfev > 45 * result.nit);
+
+        let options = MinimizeOptions {
+            maxiter: 3,
+            polish: false,
+            ..MinimizeOptions::default()
+        };
+        let result = minimize(vec![(-5.0, 5.0); 3], sum_of_squares, options);
+        assert!(!result.success);
+        assert_eq!(3, result.nit);
+        assert_eq!(4 * 45, result.nfev);
+
+        let options = MinimizeOptions { popsize: 1, ..MinimizeOptions::default() };
+        let result = minimize(vec![(-5.0, 5.0); 3], sum_of_squares, options);
+        assert_eq!(5, result.population.len());
+    }
+
+    #[test]
+    fn constant_adaptation_draws_f_once_per_generation() {
+        let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares);
+        s.adaptation = Adaptation::Constant {
+            cr: 0.7,
+            f_min_max: (0.5, 1.0),
+        };
+        let mut de = Population::new(s.with_seed(1));
+        let mut fs = Vec::new();
+        for generation in 1..11 {
+            while de.generation() < generation {
+                de.eval();
+            }
+            let trials = de.trials();
+            assert!(trials.iter().all(|t| t.cr() == 0.7 && t.f() == trials[0].f()));
+            assert!(trials[0].f() >= 0.5 && trials[0].f() < 1.0);
+            fs.push(trials[0].f());
+        }
+        fs.dedup();
+        assert_eq!(10, fs.len());
+    }
+
+    #[test]
+    fn immediate_updating_converges_faster() {
+        let mean_log_cost = |updating| {
+            let total: f64 = (0..5)
+                .map(|seed| {
+                    let mut s = Settings::default(vec![(-10.0, 10.0); 10], |pos: &[f64]| {
+                            pos.iter().fold(0.0, |sum, x| sum + x * x)
+                        })
+                        .with_seed(seed);
+                    s.pop_size = 30;
+                    s.updating = updating;
+                    let mut de = Population::new(s);
+                    de.iter().nth(6000);
+                    assert_eq!(6001, de.num_cost_evaluations());
+                    de.best().unwrap().0.log10()
+                })
+                .sum();
+            total / 5.0
+        };
+        assert!(mean_log_cost(Updating::Immediate) < mean_log_cost(Updating::Deferred) - 0.5);
+    }
+
+    #[test]
+    fn restarts_keep_the_best_and_grow_the_population() {
+        let mut s = Settings::default(vec![(-10.0, 10.0); 3], sum_of_squares).with_seed(3);
+        s.pop_size = 10;
+        let strategy = RestartStrategy {
+            stagnation_generations: 10,
+            position_tolerance: 1e-3,
+            pop_size_factor: 2.0,
+            max_pop_size: 30,
+        };
+        let mut de = Restarts::new(s, strategy);
+        de.iter().nth(20000);
+
+        let restarts = de.restarts();
+        assert!(restarts.len() >= 3);
+        assert_eq!(10, restarts[0].pop_size);
+        assert_eq!(20, restarts[1].pop_size);
+        assert!(restarts[2..].iter().all(|r| r.pop_size == 30));
+        assert!(restarts.iter().map(|r| r.num_cost_evaluations).sum::<usize>() < 20001);
+        assert_eq!(20001, de.num_cost_evaluations());
+
+        let best = *de.best().unwrap().0;
+        assert!(best < 1e-3);
+        assert!(restarts.iter().all(|r| best <= r.best_cost.unwrap()));
+    }
+
+    #[test]
+    fn restarted_l_shade_reduces_each_run_from_the_start() {
+        let s = Settings::l_shade(vec![(-10.0, 10.0); 3], sum_of_squares, 2000).with_seed(1);
+        let strategy = RestartStrategy {
+            pop_size_factor: 1.0,
+            ..RestartStrategy::default()
+        };
+        let mut de = Restarts::new(s, strategy);
+        while de.restarts().len() < 2 || de.population().generation() < 1 {
+            de.eval();
+        }
+        // the second run has only used a few evaluations of its budget.
+        assert!(de.num_cost_evaluations() > 2000);
+        assert!(de.population().pop_size() > 50);
+    }
+
+    #[test]
+    fn mixed_variables_stay_valid_and_decode() {
+        let space = SearchSpace::new(vec![Variable::Continuous(-5.0, 5.0),
+                                          Variable::Integer(-3, 7),
+                                          Variable::Categorical(4)]);
+        let decoder = space.clone();
+        let cost = move |pos: &[f32]| match decoder.decode(pos)[..] {
+            [Value::Continuous(x), Value::Integer(n), Value::Categorical(c)] => {
+                let penalty = [3.0, 1.0, 0.0, 2.0][c];
+                (x - 1.5) * (x - 1.5) + ((n - 5) * (n - 5)) as f64 + penalty
+            }
+            _ => unreachable!(),
+        };
+        let mut s = Settings::mixed(space, cost).with_seed(5);
+        s.pop_size = 20;
+        let mut de = Population::new(s);
+        for _ in 0..100 {
+            de.iter().next();
+            for trial in de.trials() {
+                let pos = trial.pos();
+                assert_eq!(pos[1], pos[1].round());
+                assert!(-3.0 <= pos[1] && pos[1] <= 7.0);
+                assert!(pos[2] == 0.0 || pos[2] == 1.0 || pos[2] == 2.0 || pos[2] == 3.0);
+            }
+        }
+        de.polish(LocalSearch::NelderMead, 200);
+
+        let (cost, values) = de.best_values().unwrap();
+        assert!(*cost < 1e-4);
+        match values[..] {
+            [Value::Continuous(x), Value::Integer(n), Value::Categorical(c)] => {
+                assert!((x - 1.5).abs() < 1e-2);
+                assert_eq!(5, n);
+                assert_eq!(2, c);
+            }
+            _ => panic!("wrong values {:?}", values),
+        }
+    }
+
+    #[test]
+    fn mixed_variables_without_random_donors() {
+        // moves towards the best, without any random individuals.
+        struct TargetToBest;
+        impl MutationStrategy for TargetToBest {
+            fn num_random(&self) -> usize {
+                0
+            }
+            fn mutate(&self, donors: &Donors<f32>, f: f32, mutant: &mut [f32]) {
+                for (d, m) in mutant.iter_mut().enumerate() {
+                    *m = donors.target[d] + f * (donors.best[d] - donors.target[d]);
+                }
+            }
+        }
+
+        let space = SearchSpace::new(vec![Variable::Integer(-3, 7), Variable::Categorical(4)]);
+        let mut s = Settings::mixed(space, sum_of_squares).with_seed(1);
+        s.pop_size = 10;
+        s.mutation = Box::new(TargetToBest);
+        let mut de = Population::new(s);
+        de.iter().nth(1000);
+        for trial in de.trials() {
+            let pos = trial.pos();
+            assert!(pos[1] == 0.0 || pos[1] == 1.0 || pos[1] == 2.0 || pos[1] == 3.0);
+        }
+    }
 }
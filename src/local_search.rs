@@ -0,0 +1,321 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Derivative-free local search for polishing the best solution.
+
+use std::cmp::Ordering;
+use rand::Rng;
+use fallible;
+use noise::Samples;
+use super::{compare, Float, Individual, Population};
+
+/// A derivative-free local search method. Both methods only compare costs,
+/// so they work with any cost type, and with constraints.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LocalSearch {
+    /// The Nelder-Mead simplex method, with the standard coefficients.
+    NelderMead,
+
+    /// Compass search: tries a step in both directions of each dimension,
+    /// moves to the first improvement, and halves the steps when there is
+    /// none.
+    Compass,
+}
+
+/// Periodic local search on the best individual during the run, which
+/// turns the DE into a memetic algorithm. See `Settings::memetic`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Memetic {
+    /// The local search method.
+    pub method: LocalSearch,
+
+    /// The local search runs after every this many generations.
+    pub generations: usize,
+
+    /// Maximum number of cost evaluations of each local search.
+    pub max_evaluations: usize,
+}
+
+impl<F, R, C, T> Population<F, R, C, T>
+    where F: Fn(&[T]) -> C,
+          R: Rng,
+          C: PartialOrd + Clone,
+          T: Float
+{
+    /// Improves the best solution found so far with a local search, like
+    /// the `polish` option of scipy's `differential_evolution`. At most
+    /// `max_evaluations` positions are evaluated, and they are counted in
+    /// `num_cost_evaluations()`. Positions never leave the hard `bounds`.
+    /// The initial step sizes are the spread of the population in each
+    /// dimension.
+    ///
+    /// The improved solution replaces the global best in the population.
+    /// Returns true if the best solution has been improved.
+    pub fn polish(&mut self, method: LocalSearch, max_evaluations: usize) -> bool {
+        let idx = match self.best_idx {
+            Some(idx) => idx,
+            None => return false,
+        };
+        let start = self.best_individual().unwrap().clone();
+        let steps = self.initial_steps();
+
+        let best = {
+            let mut search = Search {
+                pop: self,
+                remaining: max_evaluations,
+                best: start.clone(),
+            };
+            match method {
+                LocalSearch::NelderMead => search.nelder_mead(start.clone(), &steps),
+                LocalSearch::Compass => search.compass(start.clone(), steps),
+            };
+            if !search.is_better(&search.best, &start) {
+                return false;
+            }
+            search.best
+        };
+
+        let ind = &mut self.best[idx];
+        ind.pos = best.pos;
+        ind.cost = best.cost;
+        ind.constraints = best.constraints;
+        ind.samples = best.samples;
+        self.best_cost_cache = ind.cost.clone();
+        self.best_constraints_cache = ind.constraints.clone();
+        true
+    }
+
+    // Spread of the evaluated positions in each dimension. When the
+    // population has collapsed, a thousandth of min_max_pos is used.
+    fn initial_steps(&self) -> Vec<f64> {
+        self.settings
+            .min_max_pos
+            .iter()
+            .enumerate()
+            .map(|(d, &(min, max))| {
+                let (lo, hi) = self.best
+                    .iter()
+                    .filter(|ind| ind.cost.is_some())
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), ind| {
+                        let x = ind.pos[d].to_f64();
+                        (lo.min(x), hi.max(x))
+                    });
+                if hi > lo {
+                    hi - lo
+                } else {
+                    1e-3 * (max - min).to_f64()
+                }
+            })
+            .collect()
+    }
+}
+
+// State of a single local search. The methods stop as soon as the
+// evaluation budget is used up.
+struct Search<'a, F, R, C, T>
+    where F: 'a + Fn(&[T]) -> C,
+          R: 'a + Rng,
+          C: 'a + PartialOrd + Clone,
+          T: 'a + Float
+{
+    pop: &'a mut Population<F, R, C, T>,
+    remaining: usize,
+
+    // best point evaluated so far.
+    best: Individual<C, T>,
+}
+
+impl<'a, F, R, C, T> Search<'a, F, R, C, T>
+    where F: 'a + Fn(&[T]) -> C,
+          R: 'a + Rng,
+          C: 'a + PartialOrd + Clone,
+          T: 'a + Float
+{
+    // Evaluates the position, clamped into the bounds. Returns None when the
+    // budget is used up.
+    fn evaluate(&mut self, pos: Vec<T>) -> Option<Individual<C, T>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let pos = self.clamp(pos);
+        let pop = &mut *self.pop;
+        let (cost, num_retries) = fallible::evaluate(&pop.settings.cost_function,
+                                                     &pos,
+                                                     pop.settings.max_retries);
+        pop.num_failed_evaluations += num_retries;
+        pop.count_evaluation(&cost);
+        let constraints = match pop.settings.constraints {
+            Some(ref constraints) => constraints(&pos),
+            None => Vec::new(),
+        };
+        let point = Individual {
+            pos,
+            samples: Samples::first(&cost, &pop.settings.noise),
+            cost: Some(cost),
+            cr: 0.0,
+            f: 0.0,
+            constraints,
+        };
+        if self.is_better(&point, &self.best) {
+            self.best = point.clone();
+        }
+        Some(point)
+    }
+
+    // Moves the position into the bounds, if there are any, and rounds
+    // the integer and categorical variables.
+    fn clamp(&self, pos: Vec<T>) -> Vec<T> {
+        let mut pos = self.clamp_bounds(pos);
+        if let Some(ref space) = self.pop.settings.search_space {
+            space.round(&mut pos);
+        }
+        pos
+    }
+
+    fn clamp_bounds(&self, pos: Vec<T>) -> Vec<T> {
+        match self.pop.settings.bounds {
+            Some(ref bounds) => {
+                pos.iter()
+                    .zip(bounds.iter())
+                    .map(|(&x, &(min, max))| if x < min {
+                        min
+                    } else if x > max {
+                        max
+                    } else {
+                        x
+                    })
+                    .collect()
+            }
+            None => pos,
+        }
+    }
+
+    fn is_better(&self, a: &Individual<C, T>, b: &Individual<C, T>) -> bool {
+        self.ordering(a, b) == Ordering::Less
+    }
+
+    fn ordering(&self, a: &Individual<C, T>, b: &Individual<C, T>) -> Ordering {
+        compare(&a.cost, &a.constraints, &b.cost, &b.constraints, self.pop.tolerance())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    fn nelder_mead(&mut self, start: Individual<C, T>, steps: &[f64]) -> Option<()> {
+        let dim = start.pos.len();
+        let mut simplex = Vec::with_capacity(dim + 1);
+        for (d, &step) in steps.iter().enumerate() {
+            // step away from a bound, so that the simplex doesn't collapse.
+            let mut x = start.pos[d].to_f64() + step;
+            if let Some(ref bounds) = self.pop.settings.bounds {
+                if x > bounds[d].1.to_f64() {
+                    x -= 2.0 * step;
+                }
+            }
+            let mut pos = start.pos.clone();
+            pos[d] = T::from_f64(x);
+            simplex.push(self.evaluate(pos)?);
+        }
+        simplex.push(start);
+
+        loop {
+            simplex.sort_by(|a, b| self.ordering(a, b));
+            if simplex.iter().all(|p| p.pos == simplex[0].pos) {
+                return Some(());
+            }
+
+            let centroid: Vec<f64> = (0..dim)
+                .map(|d| simplex[..dim].iter().map(|p| p.pos[d].to_f64()).sum::<f64>() / dim as f64)
+                .collect();
+            let towards = |from: &[T], factor: f64| -> Vec<T> {
+                centroid.iter()
+                    .zip(from.iter())
+                    .map(|(&c, &x)| T::from_f64(c + factor * (x.to_f64() - c)))
+                    .collect()
+            };
+
+            let reflected = self.evaluate(towards(&simplex[dim].pos, -1.0))?;
+            if self.is_better(&reflected, &simplex[0]) {
+                let expanded = self.evaluate(towards(&simplex[dim].pos, -2.0))?;
+                simplex[dim] = if self.is_better(&expanded, &reflected) {
+                    expanded
+                } else {
+                    reflected
+                };
+                continue;
+            }
+            if self.is_better(&reflected, &simplex[dim - 1]) {
+                simplex[dim] = reflected;
+                continue;
+            }
+
+            // contraction, outside or inside of the simplex.
+            let is_outside = self.is_better(&reflected, &simplex[dim]);
+            let contracted = if is_outside {
+                self.evaluate(towards(&reflected.pos, 0.5))?
+            } else {
+                self.evaluate(towards(&simplex[dim].pos, 0.5))?
+            };
+            let limit = if is_outside { &reflected } else { &simplex[dim] };
+            if self.is_better(&contracted, limit) {
+                simplex[dim] = contracted;
+                continue;
+            }
+
+            // shrink towards the best vertex.
+            let mut is_changed = false;
+            for i in 1..dim + 1 {
+                let pos: Vec<T> = simplex[0].pos
+                    .iter()
+                    .zip(simplex[i].pos.iter())
+                    .map(|(&b, &x)| T::from_f64(b.to_f64() + 0.5 * (x.to_f64() - b.to_f64())))
+                    .collect();
+                if pos != simplex[i].pos {
+                    simplex[i] = self.evaluate(pos)?;
+                    is_changed = true;
+                }
+            }
+            if !is_changed {
+                return Some(());
+            }
+        }
+    }
+
+    fn compass(&mut self, start: Individual<C, T>, mut steps: Vec<f64>) -> Option<()> {
+        let mut center = start;
+        loop {
+            let mut num_trials = 0;
+            let mut improved = None;
+            'search: for (d, &step) in steps.iter().enumerate() {
+                for &sign in &[1.0, -1.0] {
+                    let mut pos = center.pos.clone();
+                    pos[d] = T::from_f64(pos[d].to_f64() + sign * step);
+                    let pos = self.clamp(pos);
+                    if pos == center.pos {
+                        continue;
+                    }
+                    num_trials += 1;
+                    let trial = self.evaluate(pos)?;
+                    if self.is_better(&trial, &center) {
+                        improved = Some(trial);
+                        break 'search;
+                    }
+                }
+            }
+            match improved {
+                Some(trial) => center = trial,
+                None if num_trials == 0 => return Some(()),
+                None => {
+                    for step in &mut steps {
+                        *step *= 0.5;
+                    }
+                }
+            }
+        }
+    }
+}
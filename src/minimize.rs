@@ -0,0 +1,184 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A one-call interface similar to scipy's `differential_evolution`.
+
+use crossover::{self, Crossover};
+use mutation::{self, MutationStrategy};
+use termination::{Any, CostSpread, MaxGenerations, Reason};
+use super::{Adaptation, Float, Initialization, LocalSearch, Population, Settings, Updating,
+            XorShift128};
+
+/// Options of `minimize`, named and with the same defaults as the
+/// arguments of scipy's `differential_evolution`.
+pub struct MinimizeOptions<T = f32> {
+    /// The mutation strategy, scipy's default `best1bin` is
+    /// `mutation::Best1` with `crossover::Binomial`.
+    pub strategy: Box<dyn MutationStrategy<T>>,
+
+    /// The crossover operator, the `bin` or `exp` part of scipy's strategy.
+    pub crossover: Box<dyn Crossover<T>>,
+
+    /// Maximum number of generations after the initial population.
+    pub maxiter: usize,
+
+    /// The population size is `popsize` times the number of dimensions,
+    /// but at least 5.
+    pub popsize: usize,
+
+    /// Relative tolerance for convergence: stops when the standard
+    /// deviation of the costs is at most `atol + tol * |mean|`.
+    pub tol: f64,
+
+    /// Absolute tolerance for convergence, see `tol`.
+    pub atol: f64,
+
+    /// Range `(min, max)` of the amplification factor `f`, with
+    /// `min <= max`. Each generation gets a random value from this range
+    /// (dithering). Use `(f, f)` for a constant.
+    pub mutation: (f32, f32),
+
+    /// The crossover probability `cr`.
+    pub recombination: f32,
+
+    /// Seed for reproducible results, random if `None`.
+    pub seed: Option<u64>,
+
+    /// Polishes the best solution with a Nelder-Mead search at the end.
+    /// scipy uses L-BFGS-B, which needs gradients.
+    pub polish: bool,
+
+    /// How the initial population is sampled.
+    pub init: Initialization,
+
+    /// Whether trials replace their parents immediately, like scipy's
+    /// default `updating='immediate'`, or after each generation.
+    pub updating: Updating,
+}
+
+impl<T: Float> Default for MinimizeOptions<T> {
+    fn default() -> MinimizeOptions<T> {
+        MinimizeOptions {
+            strategy: Box::new(mutation::Best1),
+            crossover: Box::new(crossover::Binomial),
+            maxiter: 1000,
+            popsize: 15,
+            tol: 0.01,
+            atol: 0.0,
+            mutation: (0.5, 1.0),
+            recombination: 0.7,
+            seed: None,
+            polish: true,
+            init: Initialization::LatinHypercube,
+            updating: Updating::Immediate,
+        }
+    }
+}
+
+/// Result of `minimize`, like scipy's `OptimizeResult`.
+#[derive(Clone, Debug)]
+pub struct OptimizeResult<C, T = f32> {
+    /// The best position found.
+    pub x: Vec<T>,
+
+    /// The cost of `x`.
+    pub fun: C,
+
+    /// Number of cost evaluations, including the polishing.
+    pub nfev: usize,
+
+    /// Number of generations after the initial population.
+    pub nit: usize,
+
+    /// True if the population has converged, false if `maxiter` was
+    /// reached.
+    pub success: bool,
+
+    /// Why the optimization has stopped.
+    pub message: String,
+
+    /// Positions of the final population.
+    pub population: Vec<Vec<T>>,
+
+    /// Costs of the final population.
+    pub population_energies: Vec<C>,
+}
+
+/// Finds the minimum of the cost function within the bounds, which are
+/// never left. This is a convenience interface similar to scipy's
+/// `differential_evolution`, built on `Population`:
+///
+/// ```
+/// use differential_evolution::{minimize, MinimizeOptions};
+///
+/// let result = minimize(vec![(-5.0, 5.0); 2],
+///                       |x: &[f64]| x.iter().fold(0.0, |sum, x| sum + x * x),
+///                       MinimizeOptions { seed: Some(1), ..MinimizeOptions::default() });
+/// assert!(result.success);
+/// assert!(result.fun < 1e-6);
+/// ```
+pub fn minimize<F, C, T>(bounds: Vec<(T, T)>,
+                         cost_function: F,
+                         options: MinimizeOptions<T>)
+                         -> OptimizeResult<C, T>
+    where F: Fn(&[T]) -> C,
+          C: PartialOrd + Clone + Into<f64>,
+          T: Float
+{
+    assert!(options.mutation.0 <= options.mutation.1,
+            "mutation range must not be reversed");
+    let dim = bounds.len();
+    let mut s = Settings::default(bounds.clone(), cost_function);
+    s.bounds = Some(bounds);
+    s.pop_size = (options.popsize * dim).max(5);
+    s.mutation = options.strategy;
+    s.crossover = options.crossover;
+    s.initialization = options.init;
+    s.updating = options.updating;
+    s.adaptation = Adaptation::Constant {
+        cr: options.recombination,
+        f_min_max: options.mutation,
+    };
+
+    let rng = match options.seed {
+        Some(seed) => XorShift128::seed_from_u64(seed),
+        None => XorShift128::new(),
+    };
+    let mut pop = Population::new(s.with_rng(rng));
+    // the initial population is not counted as an iteration, and
+    // convergence takes precedence when both fire in the same generation.
+    let reason = pop.run_until(Any(vec![Box::new(CostSpread {
+                                            tol: options.tol,
+                                            atol: options.atol,
+                                        }),
+                                        Box::new(MaxGenerations(options.maxiter + 1))]));
+    let nit = pop.generation();
+    if options.polish {
+        pop.polish(LocalSearch::NelderMead, 200 * dim);
+    }
+
+    let (success, message) = match reason {
+        Reason::Converged => (true, "Optimization terminated successfully."),
+        _ => (false, "Maximum number of iterations has been exceeded."),
+    };
+    let (fun, x) = {
+        let (cost, pos) = pop.best().unwrap();
+        (cost.clone(), pos.to_vec())
+    };
+    let individuals = pop.individuals();
+    OptimizeResult {
+        x,
+        fun,
+        nfev: pop.num_cost_evaluations(),
+        nit,
+        success,
+        message: message.to_string(),
+        population: individuals.iter().map(|ind| ind.pos().to_vec()).collect(),
+        population_energies: individuals.iter().map(|ind| ind.cost().unwrap().clone()).collect(),
+    }
+}
@@ -0,0 +1,311 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Multi-objective optimization with GDE3.
+
+use std::cmp::Ordering;
+use rand::Rng;
+use super::{Adaptation, Float, Individual, Initialization, Population, Settings, Updating};
+use fallible::is_valid_cost;
+
+/// Multi-objective optimizer using GDE3, as described in "GDE3: The third
+/// Evolution Step of Generalized Differential Evolution".
+///
+/// The cost function returns a vector of objectives, all of which are
+/// minimized. A trial replaces its parent when it is at least as good in
+/// all objectives, and it is discarded when the parent dominates it. When
+/// neither dominates the other, both are kept, and the population is
+/// reduced back to its size with non-dominated sorting and crowding
+/// distance.
+///
+/// Mutation, crossover and the jDE parameter adaptation are the same as in
+/// `Population`. Strategies that use the global best use an arbitrary
+/// non-dominated individual instead, so `mutation::Rand1` should be
+/// preferred. JADE, SHADE and constant adaptation, constraints, population
+/// size reduction, observers, local search, noise handling, immediate
+/// updating and opposition-based initialization are not supported.
+///
+/// The non-dominated solutions found so far are kept in a Pareto archive,
+/// which holds at most `pop_size` solutions. When it becomes larger, the
+/// most crowded solutions are removed. Failed evaluations, e.g. with a NaN
+/// objective, never enter the archive.
+pub struct Gde3<F, R, T = f32>
+    where F: Fn(&[T]) -> Vec<T>,
+          R: Rng,
+          T: Float
+{
+    pop: Population<F, R, Vec<T>, T>,
+
+    // non-dominated (objectives, position) pairs found so far.
+    front: Vec<(Vec<T>, Vec<T>)>,
+}
+
+impl<F, R, T> Gde3<F, R, T>
+    where F: Fn(&[T]) -> Vec<T>,
+          R: Rng,
+          T: Float
+{
+    /// Creates a new multi-objective optimizer based on the given settings.
+    pub fn new(s: Settings<F, R, Vec<T>, T>) -> Gde3<F, R, T> {
+        assert!(s.adaptation == Adaptation::Jde,
+                "GDE3 only supports jDE adaptation");
+        assert!(s.constraints.is_none(), "GDE3 does not support constraints");
+        assert!(s.pop_reduction.is_none(),
+                "GDE3 does not support population size reduction");
+        assert!(s.observer.is_none(), "GDE3 does not support observers");
+        assert!(s.memetic.is_none(), "GDE3 does not support local search");
+        assert!(s.noise.is_none(), "GDE3 does not support noise handling");
+        assert!(s.updating == Updating::Deferred,
+                "GDE3 does not support immediate updating");
+        assert!(s.initial_candidates.iter().all(|c| c.1.is_none()),
+                "GDE3 does not support initial candidates with known costs");
+        assert!(s.initialization != Initialization::Opposition,
+                "GDE3 does not support opposition-based initialization");
+        Gde3 {
+            pop: Population::new(s),
+            front: Vec::new(),
+        }
+    }
+
+    /// Performs a single cost evaluation, and performs the GDE3 selection
+    /// and evolves the population if the whole population has been
+    /// evaluated.
+    pub fn eval(&mut self) {
+        if 0 == self.pop.pop_countdown {
+            self.select();
+            self.pop.generation += 1;
+            self.pop.update_positions();
+            self.pop.pop_countdown = self.pop.curr.len();
+        }
+
+        self.pop.pop_countdown -= 1;
+        let idx = self.pop.pop_countdown;
+        let cost = self.pop.evaluate(idx);
+        if is_valid_cost(&cost) {
+            self.pop.num_cost_evaluations += 1;
+            self.add_to_front(&cost, idx);
+        } else {
+            self.pop.num_failed_evaluations += 1;
+        }
+        self.pop.curr[idx].cost = Some(cost);
+    }
+
+    /// Gets the non-dominated solutions found so far, as tuples of the
+    /// objective values and the position.
+    pub fn front(&self) -> Vec<(&[T], &[T])> {
+        self.front.iter().map(|(cost, pos)| (&cost[..], &pos[..])).collect()
+    }
+
+    /// Gets the total number of times the cost function has been evaluated.
+    /// Failed evaluations are not included.
+    pub fn num_cost_evaluations(&self) -> usize {
+        self.pop.num_cost_evaluations
+    }
+
+    /// Gets the number of failed evaluations, including the retried ones.
+    pub fn num_failed_evaluations(&self) -> usize {
+        self.pop.num_failed_evaluations
+    }
+
+    /// Gets an iterator for this optimizer. Each call to `next()` performs
+    /// one cost evaluation, and returns the number of solutions in the
+    /// Pareto archive.
+    pub fn iter(&mut self) -> Gde3Iter<'_, F, R, T> {
+        Gde3Iter { gde3: self }
+    }
+
+    // Inserts the evaluated curr individual into the Pareto archive, unless
+    // it is dominated.
+    fn add_to_front(&mut self, cost: &[T], idx: usize) {
+        if self.front.iter().any(|(c, _)| weakly_dominates(c, cost)) {
+            return;
+        }
+        self.front.retain(|(c, _)| !dominates(cost, c));
+        self.front.push((cost.to_vec(), self.pop.curr[idx].pos.clone()));
+
+        if self.front.len() > self.pop.settings.pop_size {
+            let distances = {
+                let costs: Vec<&[T]> = self.front.iter().map(|(c, _)| &c[..]).collect();
+                crowding_distances(&costs)
+            };
+            let most_crowded = (0..distances.len())
+                .min_by(|&a, &b| distances[a].partial_cmp(&distances[b]).unwrap_or(Ordering::Equal))
+                .unwrap();
+            self.front.swap_remove(most_crowded);
+        }
+    }
+
+    // GDE3 selection between each trial in curr and its parent in best.
+    fn select(&mut self) {
+        let pop = &mut self.pop;
+        let pop_size = pop.curr.len();
+
+        let mut extra = Vec::new();
+        for i in 0..pop_size {
+            let curr = &mut pop.curr[i];
+            let best = &mut pop.best[i];
+            let is_swapping = match (&curr.cost, &best.cost) {
+                (Some(_), None) => true,
+                // failed evaluations never survive a valid one.
+                (Some(c), Some(b)) if !is_valid_cost(c) || !is_valid_cost(b) => {
+                    !is_valid_cost(b)
+                }
+                (Some(c), Some(b)) => {
+                    if weakly_dominates(c, b) {
+                        true
+                    } else {
+                        if !dominates(b, c) {
+                            // neither dominates, both survive for now.
+                            extra.push(curr.clone());
+                        }
+                        false
+                    }
+                }
+                _ => false,
+            };
+            if is_swapping {
+                std::mem::swap(curr, best);
+            }
+        }
+
+        if !extra.is_empty() {
+            let mut candidates: Vec<Individual<Vec<T>, T>> = pop.best.drain(..).collect();
+            candidates.append(&mut extra);
+            let mut keep = {
+                let costs: Vec<&[T]> = candidates.iter()
+                    .map(|ind| &ind.cost.as_ref().unwrap()[..])
+                    .collect();
+                select_by_rank_and_crowding(&costs, pop_size)
+            };
+            keep.sort();
+            let mut is_kept = vec![false; candidates.len()];
+            for &k in &keep {
+                is_kept[k] = true;
+            }
+            let mut i = 0;
+            candidates.retain(|_| {
+                i += 1;
+                is_kept[i - 1]
+            });
+            pop.best = candidates;
+        }
+
+        // strategies that use the global best get some non-dominated
+        // individual, or any individual when all evaluations have failed.
+        let best_idx = {
+            let costs: Vec<&[T]> = pop.best
+                .iter()
+                .map(|ind| &ind.cost.as_ref().unwrap()[..])
+                .collect();
+            (0..costs.len()).find(|&i| {
+                is_valid_cost(&costs[i]) && !costs.iter().any(|c| dominates(c, costs[i]))
+            })
+        };
+        pop.best_idx = best_idx.or(Some(0));
+    }
+}
+
+/// Iterator for GDE3, to perform a single cost evaluation every time
+/// `next()` is called.
+pub struct Gde3Iter<'a, F, R, T = f32>
+    where F: 'a + Fn(&[T]) -> Vec<T>,
+          R: 'a + Rng,
+          T: 'a + Float
+{
+    gde3: &'a mut Gde3<F, R, T>,
+}
+
+impl<'a, F, R, T> Iterator for Gde3Iter<'a, F, R, T>
+    where F: 'a + Fn(&[T]) -> Vec<T>,
+          R: 'a + Rng,
+          T: 'a + Float
+{
+    type Item = usize;
+
+    /// Forwards to `eval()`, and returns the size of the Pareto archive.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.gde3.eval();
+        Some(self.gde3.front.len())
+    }
+}
+
+// True if a is not worse than b in all objectives.
+fn weakly_dominates<T: Float>(a: &[T], b: &[T]) -> bool {
+    a.iter().zip(b.iter()).all(|(x, y)| x <= y)
+}
+
+// True if a is not worse than b in all objectives, and better in at least one.
+fn dominates<T: Float>(a: &[T], b: &[T]) -> bool {
+    weakly_dominates(a, b) && a.iter().zip(b.iter()).any(|(x, y)| x < y)
+}
+
+// Crowding distance of each point: the sum over all objectives of the
+// normalized distance between its neighbours. Boundary points get infinity.
+fn crowding_distances<T: Float>(costs: &[&[T]]) -> Vec<f64> {
+    let n = costs.len();
+    let mut distances = vec![0.0; n];
+    if n == 0 {
+        return distances;
+    }
+    let mut order: Vec<usize> = (0..n).collect();
+    for m in 0..costs[0].len() {
+        add_crowding_distances(costs, m, &mut order, &mut distances);
+    }
+    distances
+}
+
+// Adds the crowding distances of objective m. order is used as scratch space.
+fn add_crowding_distances<T: Float>(costs: &[&[T]],
+                                   m: usize,
+                                   order: &mut [usize],
+                                   distances: &mut [f64]) {
+    let n = costs.len();
+    let objective = |i: usize| costs[i][m].to_f64();
+    order.sort_by(|&a, &b| objective(a).partial_cmp(&objective(b)).unwrap_or(Ordering::Equal));
+    let min = objective(order[0]);
+    let max = objective(order[n - 1]);
+    distances[order[0]] = f64::INFINITY;
+    distances[order[n - 1]] = f64::INFINITY;
+    if max <= min {
+        return;
+    }
+    for k in 1..n.saturating_sub(1) {
+        distances[order[k]] += (objective(order[k + 1]) - objective(order[k - 1])) / (max - min);
+    }
+}
+
+// Selects `count` points by non-dominated sorting. The last front that does
+// not fit completely is cut by crowding distance. Failed evaluations can't
+// be compared, so they are only selected when there are not enough others.
+fn select_by_rank_and_crowding<T: Float>(costs: &[&[T]], count: usize) -> Vec<usize> {
+    let (mut remaining, invalid): (Vec<usize>, Vec<usize>) =
+        (0..costs.len()).partition(|&i| is_valid_cost(&costs[i]));
+    let mut selected = Vec::with_capacity(count);
+    while selected.len() < count && !remaining.is_empty() {
+        let (front, rest): (Vec<usize>, Vec<usize>) = remaining.iter().partition(|&&i| {
+            !remaining.iter().any(|&j| dominates(costs[j], costs[i]))
+        });
+        remaining = rest;
+
+        if selected.len() + front.len() <= count {
+            selected.extend(front);
+        } else {
+            let front_costs: Vec<&[T]> = front.iter().map(|&i| costs[i]).collect();
+            let distances = crowding_distances(&front_costs);
+            let mut order: Vec<usize> = (0..front.len()).collect();
+            order.sort_by(|&a, &b| {
+                distances[b].partial_cmp(&distances[a]).unwrap_or(Ordering::Equal)
+            });
+            let missing = count - selected.len();
+            selected.extend(order[..missing].iter().map(|&k| front[k]));
+        }
+    }
+    let missing = count - selected.len();
+    selected.extend(invalid.into_iter().take(missing));
+    selected
+}
@@ -0,0 +1,185 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Mutation strategies that create the mutant vector of the DE.
+//!
+//! The naming follows the usual `DE/x/y` scheme, where `x` is the
+//! base vector and `y` the number of difference vectors. See "A
+//! Comparative Study of Differential Evolution Variants for Global
+//! Optimization (2006)" for an overview.
+
+use float::Float;
+
+/// Position vectors that are available to a mutation strategy for
+/// building a mutant.
+pub struct Donors<'a, T: 'a> {
+    /// Position of the individual the mutant is created for.
+    pub target: &'a [T],
+
+    /// Position of the global best individual.
+    pub best: &'a [T],
+
+    /// Position of an individual randomly chosen from the top
+    /// `pbest_fraction()` of the population. Equals `best` if the
+    /// strategy does not use it.
+    pub pbest: &'a [T],
+
+    /// Distinct, randomly chosen individuals. Contains exactly
+    /// `num_random()` positions.
+    pub random: &'a [&'a [T]],
+}
+
+/// Creates a mutant vector from the donors of the population. Strategies
+/// are `Send`, so that a `Population` can be moved to another thread.
+pub trait MutationStrategy<T = f32>: Send {
+    /// Number of distinct random individuals the strategy needs.
+    fn num_random(&self) -> usize;
+
+    /// Fraction of the best individuals from which `Donors::pbest` is
+    /// chosen. `None` if the strategy does not need it.
+    fn pbest_fraction(&self) -> Option<f32> {
+        None
+    }
+
+    /// Writes the mutant into `mutant`, using `f` as the amplification
+    /// factor of the difference vectors.
+    fn mutate(&self, donors: &Donors<T>, f: f32, mutant: &mut [T]);
+}
+
+/// DE/rand/1: `r0 + f * (r1 - r2)`. The most widely used strategy, and a
+/// good default.
+pub struct Rand1;
+
+impl<T: Float> MutationStrategy<T> for Rand1 {
+    fn num_random(&self) -> usize {
+        3
+    }
+
+    fn mutate(&self, donors: &Donors<T>, f: f32, mutant: &mut [T]) {
+        let f = T::from_f64(f as f64);
+        let r = donors.random;
+        for d in 0..mutant.len() {
+            mutant[d] = r[0][d] + f * (r[1][d] - r[2][d]);
+        }
+    }
+}
+
+/// DE/best/1: `best + f * (r0 - r1)`. Converges fast, but is prone to
+/// premature convergence on multimodal problems.
+pub struct Best1;
+
+impl<T: Float> MutationStrategy<T> for Best1 {
+    fn num_random(&self) -> usize {
+        2
+    }
+
+    fn mutate(&self, donors: &Donors<T>, f: f32, mutant: &mut [T]) {
+        let f = T::from_f64(f as f64);
+        let r = donors.random;
+        for d in 0..mutant.len() {
+            mutant[d] = donors.best[d] + f * (r[0][d] - r[1][d]);
+        }
+    }
+}
+
+/// DE/rand/2: `r0 + f * (r1 - r2) + f * (r3 - r4)`. More explorative
+/// than DE/rand/1.
+pub struct Rand2;
+
+impl<T: Float> MutationStrategy<T> for Rand2 {
+    fn num_random(&self) -> usize {
+        5
+    }
+
+    fn mutate(&self, donors: &Donors<T>, f: f32, mutant: &mut [T]) {
+        let f = T::from_f64(f as f64);
+        let r = donors.random;
+        for d in 0..mutant.len() {
+            mutant[d] = r[0][d] + f * (r[1][d] - r[2][d]) + f * (r[3][d] - r[4][d]);
+        }
+    }
+}
+
+/// DE/best/2: `best + f * (r0 - r1) + f * (r2 - r3)`.
+pub struct Best2;
+
+impl<T: Float> MutationStrategy<T> for Best2 {
+    fn num_random(&self) -> usize {
+        4
+    }
+
+    fn mutate(&self, donors: &Donors<T>, f: f32, mutant: &mut [T]) {
+        let f = T::from_f64(f as f64);
+        let r = donors.random;
+        for d in 0..mutant.len() {
+            mutant[d] = donors.best[d] + f * (r[0][d] - r[1][d]) + f * (r[2][d] - r[3][d]);
+        }
+    }
+}
+
+/// DE/current-to-best/1: `target + f * (best - target) + f * (r0 - r1)`.
+pub struct CurrentToBest1;
+
+impl<T: Float> MutationStrategy<T> for CurrentToBest1 {
+    fn num_random(&self) -> usize {
+        2
+    }
+
+    fn mutate(&self, donors: &Donors<T>, f: f32, mutant: &mut [T]) {
+        let f = T::from_f64(f as f64);
+        let r = donors.random;
+        let t = donors.target;
+        for d in 0..mutant.len() {
+            mutant[d] = t[d] + f * (donors.best[d] - t[d]) + f * (r[0][d] - r[1][d]);
+        }
+    }
+}
+
+/// DE/rand-to-best/1: `r0 + f * (best - r0) + f * (r1 - r2)`.
+pub struct RandToBest1;
+
+impl<T: Float> MutationStrategy<T> for RandToBest1 {
+    fn num_random(&self) -> usize {
+        3
+    }
+
+    fn mutate(&self, donors: &Donors<T>, f: f32, mutant: &mut [T]) {
+        let f = T::from_f64(f as f64);
+        let r = donors.random;
+        for d in 0..mutant.len() {
+            mutant[d] = r[0][d] + f * (donors.best[d] - r[0][d]) + f * (r[1][d] - r[2][d]);
+        }
+    }
+}
+
+/// DE/current-to-pbest/1 as used in JADE: `target + f * (pbest - target) + f
+/// * (r0 - r1)`, where `pbest` is randomly chosen from the best `p` fraction
+/// of the population. `p` between 0.05 and 0.2 is recommended.
+pub struct CurrentToPBest1 {
+    /// Fraction of the population from which `pbest` is chosen.
+    pub p: f32,
+}
+
+impl<T: Float> MutationStrategy<T> for CurrentToPBest1 {
+    fn num_random(&self) -> usize {
+        2
+    }
+
+    fn pbest_fraction(&self) -> Option<f32> {
+        Some(self.p)
+    }
+
+    fn mutate(&self, donors: &Donors<T>, f: f32, mutant: &mut [T]) {
+        let f = T::from_f64(f as f64);
+        let r = donors.random;
+        let t = donors.target;
+        for d in 0..mutant.len() {
+            mutant[d] = t[d] + f * (donors.pbest[d] - t[d]) + f * (r[0][d] - r[1][d]);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Handling of noisy cost functions.
+
+use float::Float;
+use fallible::is_valid_cost;
+
+/// Handling of noisy cost functions, e.g. Monte-Carlo simulations, see
+/// `Settings::noise`. Without it, a single lucky evaluation can become the
+/// best solution forever.
+///
+/// Survivors are re-evaluated after each generation, and the cost of an
+/// individual is the mean of all its evaluations. A trial only replaces its
+/// parent when its mean cost is significantly lower, using the noise
+/// variance estimated from the re-evaluated individuals. Re-evaluations use
+/// the cost function, so this does not work with `ask()` and `tell()`.
+pub struct NoiseHandling<C> {
+    /// Survivors are re-evaluated once per generation, until they have this
+    /// many samples.
+    pub max_samples: usize,
+
+    /// A trial replaces its parent only when its mean cost is lower by at
+    /// least this many standard errors of the difference. 2.0 corresponds
+    /// to about 98% confidence, 0.0 accepts every improvement of the mean.
+    pub significance: f64,
+
+    /// Converts a cost to `f64`, for averaging.
+    pub to_f64: fn(&C) -> f64,
+
+    /// Converts the mean back to a cost.
+    pub from_f64: fn(f64) -> C,
+}
+
+impl<C> Clone for NoiseHandling<C> {
+    fn clone(&self) -> NoiseHandling<C> {
+        *self
+    }
+}
+
+impl<C> Copy for NoiseHandling<C> {}
+
+impl<C: Float> NoiseHandling<C> {
+    /// Creates the noise handling for floating point costs.
+    pub fn new(max_samples: usize, significance: f64) -> NoiseHandling<C> {
+        NoiseHandling {
+            max_samples,
+            significance,
+            to_f64: cost_to_f64::<C>,
+            from_f64: C::from_f64,
+        }
+    }
+}
+
+fn cost_to_f64<C: Float>(cost: &C) -> f64 {
+    cost.to_f64()
+}
+
+/// Running mean and variance of the valid cost samples of an individual,
+/// with Welford's algorithm.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Samples {
+    pub(crate) n: usize,
+    pub(crate) mean: f64,
+    m2: f64,
+}
+
+impl Samples {
+    /// The samples of a single evaluation. Without noise handling, or when
+    /// the evaluation has failed, there are none.
+    pub fn first<C: PartialOrd>(cost: &C, noise: &Option<NoiseHandling<C>>) -> Samples {
+        let mut samples = Samples::default();
+        if let Some(ref noise) = *noise {
+            if is_valid_cost(cost) {
+                samples.add((noise.to_f64)(cost));
+            }
+        }
+        samples
+    }
+
+    pub fn add(&mut self, x: f64) {
+        self.n += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.n as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    // Sample variance, if there are at least two samples.
+    fn variance(&self) -> Option<f64> {
+        if self.n >= 2 {
+            Some(self.m2 / (self.n - 1) as f64)
+        } else {
+            None
+        }
+    }
+}
+
+/// Estimates the noise variance as the mean sample variance of all
+/// individuals with at least two samples.
+pub fn pooled_variance<'a, I: Iterator<Item = &'a Samples>>(samples: I) -> Option<f64> {
+    let (sum, count) = samples.filter_map(|s| s.variance())
+        .fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    if count > 0 {
+        Some(sum / count as f64)
+    } else {
+        None
+    }
+}
+
+/// True if the trial's mean is significantly lower than the parent's. As
+/// long as there is no variance estimate, any improvement is significant.
+pub fn is_significant(trial: &Samples,
+                      parent: &Samples,
+                      variance: Option<f64>,
+                      significance: f64)
+                      -> bool {
+    match variance {
+        Some(variance) => {
+            let n_trial = trial.n.max(1) as f64;
+            let n_parent = parent.n.max(1) as f64;
+            let std_error = (variance / n_trial + variance / n_parent).sqrt();
+            trial.mean + significance * std_error < parent.mean
+        }
+        None => true,
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Restarts of a stagnating population.
+
+use std::cmp::Ordering;
+use rand::Rng;
+use super::{compare, Float, Individual, Population, Settings};
+use termination::{PositionSpread, Stagnation, Termination};
+
+/// When to restart the population, and how much to grow it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RestartStrategy {
+    /// Restart when the best cost of the current run has not improved for
+    /// this many generations.
+    pub stagnation_generations: usize,
+
+    /// Restart when in each dimension the positions of all individuals lie
+    /// within this distance of each other.
+    pub position_tolerance: f64,
+
+    /// The population size is multiplied by this factor at each restart.
+    /// 2.0 gives IPOP, 1.0 keeps the size.
+    pub pop_size_factor: f64,
+
+    /// The population never grows beyond this size.
+    pub max_pop_size: usize,
+}
+
+impl Default for RestartStrategy {
+    /// IPOP: restarts after 50 generations without improvement, or when
+    /// the positions have collapsed to 1e-6, and doubles the population up
+    /// to 1000 individuals.
+    fn default() -> RestartStrategy {
+        RestartStrategy {
+            stagnation_generations: 50,
+            position_tolerance: 1e-6,
+            pop_size_factor: 2.0,
+            max_pop_size: 1000,
+        }
+    }
+}
+
+/// Summary of a finished run between two restarts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RestartStatistics<C> {
+    /// Initial population size of the run.
+    pub pop_size: usize,
+
+    /// Number of generations of the run.
+    pub num_generations: usize,
+
+    /// Number of cost evaluations of the run.
+    pub num_cost_evaluations: usize,
+
+    /// Best cost found in the run.
+    pub best_cost: Option<C>,
+}
+
+/// Restarts a `Population` with fresh random individuals whenever it
+/// stagnates or has converged, optionally with a larger population as in
+/// IPOP ("A Restart CMA Evolution Strategy With Increasing Population
+/// Size"). The best solution is kept across all restarts.
+///
+/// ```
+/// use differential_evolution::{RestartStrategy, Restarts, Settings};
+///
+/// let cost = |pos: &[f32]| pos.iter().fold(0.0, |sum, x| sum + x * x);
+/// let mut de = Restarts::new(Settings::default(vec![(-10.0, 10.0); 3], cost),
+///                            RestartStrategy::default());
+/// de.iter().nth(10000);
+/// println!("{} restarts", de.restarts().len());
+/// ```
+///
+/// A restarted run uses the same settings, except for the population
+/// size. Observers see the generations of each run counted from 1, and a
+/// `pop_reduction` budget applies to each run separately.
+pub struct Restarts<F, R, C, T = f32>
+    where F: Fn(&[T]) -> C,
+          R: Rng,
+          C: PartialOrd + Clone,
+          T: Float
+{
+    pop: Population<F, R, C, T>,
+    strategy: RestartStrategy,
+    stagnation: Stagnation<C>,
+
+    // best individual of all finished runs.
+    best: Option<Individual<C, T>>,
+    restarts: Vec<RestartStatistics<C>>,
+
+    // number of evaluations when the current run was started.
+    run_evaluations: usize,
+}
+
+impl<F, R, C, T> Restarts<F, R, C, T>
+    where F: Fn(&[T]) -> C,
+          R: Rng,
+          C: PartialOrd + Clone,
+          T: Float
+{
+    /// Creates the first population from the settings.
+    pub fn new(s: Settings<F, R, C, T>, strategy: RestartStrategy) -> Restarts<F, R, C, T> {
+        assert!(strategy.pop_size_factor >= 1.0,
+                "pop_size_factor must not shrink the population");
+        Restarts {
+            pop: Population::new(s),
+            stagnation: Stagnation::new(strategy.stagnation_generations),
+            strategy,
+            best: None,
+            restarts: Vec::new(),
+            run_evaluations: 0,
+        }
+    }
+
+    /// Performs a single cost evaluation. After a whole generation has been
+    /// evaluated, the population is restarted if it stagnates or has
+    /// converged.
+    pub fn eval(&mut self) {
+        self.pop.eval();
+        if 0 != self.pop.pop_countdown {
+            return;
+        }
+        let is_restarting = {
+            let status = self.pop.status();
+            let is_stagnating = self.stagnation.check(&status).is_some();
+            is_stagnating ||
+            PositionSpread(self.strategy.position_tolerance).check(&status).is_some()
+        };
+        if is_restarting {
+            self.restart();
+        }
+    }
+
+    // Keeps the best individual of the finished run, and starts a new one.
+    fn restart(&mut self) {
+        if self.is_run_better() {
+            self.best = self.pop.best_individual().cloned();
+        }
+        let num_cost_evaluations = self.pop.num_cost_evaluations;
+        self.restarts.push(RestartStatistics {
+            pop_size: self.pop.settings.pop_size,
+            num_generations: self.pop.generation + 1,
+            num_cost_evaluations: num_cost_evaluations - self.run_evaluations,
+            best_cost: self.pop.best_cost_cache.clone(),
+        });
+        self.run_evaluations = num_cost_evaluations;
+
+        let pop_size = self.pop.settings.pop_size as f64 * self.strategy.pop_size_factor;
+        let max_pop_size = self.strategy.max_pop_size.max(self.pop.settings.pop_size);
+        self.pop.settings.pop_size = (pop_size.round() as usize).min(max_pop_size);
+        self.pop.initialize();
+        self.stagnation = Stagnation::new(self.strategy.stagnation_generations);
+    }
+
+    // True if the best solution of the current run is better than the best
+    // of all finished runs.
+    fn is_run_better(&self) -> bool {
+        match self.best {
+            _ if self.pop.best_cost_cache.is_none() => false,
+            None => true,
+            Some(ref best) => {
+                compare(&self.pop.best_cost_cache,
+                        &self.pop.best_constraints_cache,
+                        &best.cost,
+                        &best.constraints,
+                        self.pop.tolerance()) == Some(Ordering::Less)
+            }
+        }
+    }
+
+    /// Gets a tuple of the best cost and best position found so far, over
+    /// all restarts.
+    pub fn best(&self) -> Option<(&C, &[T])> {
+        match self.best {
+            Some(ref best) if !self.is_run_better() => Some((best.cost.as_ref().unwrap(), &best.pos)),
+            _ => self.pop.best(),
+        }
+    }
+
+    /// Gets the statistics of all finished runs, in the order of the
+    /// restarts. The current run is not included.
+    pub fn restarts(&self) -> &[RestartStatistics<C>] {
+        &self.restarts
+    }
+
+    /// Gets the population of the current run.
+    pub fn population(&self) -> &Population<F, R, C, T> {
+        &self.pop
+    }
+
+    /// Gets the total number of times the cost function has been evaluated
+    /// over all restarts. Failed evaluations are not included.
+    pub fn num_cost_evaluations(&self) -> usize {
+        self.pop.num_cost_evaluations
+    }
+
+    /// Gets the number of failed evaluations over all restarts, including
+    /// the retried ones.
+    pub fn num_failed_evaluations(&self) -> usize {
+        self.pop.num_failed_evaluations
+    }
+
+    /// Gets an iterator that spans all restarts. Each call to `next()`
+    /// performs one cost evaluation.
+    pub fn iter(&mut self) -> RestartsIter<'_, F, R, C, T> {
+        RestartsIter { restarts: self }
+    }
+}
+
+/// Iterator for `Restarts`, to perform a single cost evaluation every time
+/// `next()` is called.
+pub struct RestartsIter<'a, F, R, C, T = f32>
+    where F: 'a + Fn(&[T]) -> C,
+          R: 'a + Rng,
+          C: 'a + PartialOrd + Clone,
+          T: 'a + Float
+{
+    restarts: &'a mut Restarts<F, R, C, T>,
+}
+
+impl<'a, F, R, C, T> Iterator for RestartsIter<'a, F, R, C, T>
+    where F: 'a + Fn(&[T]) -> C,
+          R: 'a + Rng,
+          C: PartialOrd + Clone,
+          T: 'a + Float
+{
+    type Item = C;
+
+    /// Forwards to `eval()`, and returns the best cost over all restarts.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.restarts.eval();
+        self.restarts.best().map(|(cost, _)| cost.clone())
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A random number generator whose state can be saved.
+
+use rand::{self, Rng, SeedableRng};
+
+/// Marsaglia's xorshift128 generator, the same algorithm as
+/// `rand::XorShiftRng`. Unlike that, its state is accessible: it can be
+/// cloned into a `Checkpoint`, and with the `serde` feature it can be
+/// serialized. Not suitable for cryptographic purposes.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct XorShift128 {
+    x: u32,
+    y: u32,
+    z: u32,
+    w: u32,
+}
+
+impl XorShift128 {
+    /// Creates a generator with a random seed from `rand::weak_rng()`.
+    pub fn new() -> XorShift128 {
+        let mut seed = [0u32; 4];
+        while seed == [0; 4] {
+            seed = rand::weak_rng().gen();
+        }
+        XorShift128::from_seed(seed)
+    }
+
+    /// Creates a generator from a 64 bit seed, expanded with splitmix64.
+    /// The same seed always produces the same sequence of numbers.
+    pub fn seed_from_u64(seed: u64) -> XorShift128 {
+        let mut state = seed;
+        let mut next = || {
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+        let (a, b) = (next(), next());
+        let seed = [a as u32, (a >> 32) as u32, b as u32, (b >> 32) as u32];
+        if seed == [0; 4] {
+            // practically impossible, but xorshift would only produce zeros.
+            return XorShift128::from_seed([1, 0, 0, 0]);
+        }
+        XorShift128::from_seed(seed)
+    }
+}
+
+impl Default for XorShift128 {
+    fn default() -> XorShift128 {
+        XorShift128::new()
+    }
+}
+
+impl Rng for XorShift128 {
+    fn next_u32(&mut self) -> u32 {
+        let x = self.x;
+        let t = x ^ (x << 11);
+        self.x = self.y;
+        self.y = self.z;
+        self.z = self.w;
+        let w = self.w;
+        self.w = w ^ (w >> 19) ^ (t ^ (t >> 8));
+        self.w
+    }
+}
+
+impl SeedableRng<[u32; 4]> for XorShift128 {
+    /// Reseeds the generator. Panics if the seed is all zeros.
+    fn reseed(&mut self, seed: [u32; 4]) {
+        assert!(seed != [0; 4], "XorShift128.reseed called with an all zero seed");
+        self.x = seed[0];
+        self.y = seed[1];
+        self.z = seed[2];
+        self.w = seed[3];
+    }
+
+    /// Creates a generator from the seed. Panics if the seed is all zeros.
+    fn from_seed(seed: [u32; 4]) -> XorShift128 {
+        assert!(seed != [0; 4], "XorShift128::from_seed called with an all zero seed");
+        XorShift128 {
+            x: seed[0],
+            y: seed[1],
+            z: seed[2],
+            w: seed[3],
+        }
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Search spaces with integer and categorical variables.
+
+use rand::Rng;
+use rand::distributions::{IndependentSample, Range};
+use float::Float;
+
+/// Probability that a categorical variable taken from the mutant gets a
+/// uniformly random category instead of the category of a random donor.
+const RANDOM_CATEGORY_PROBABILITY: f64 = 0.1;
+
+/// The type and range of a single dimension of the search space.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Variable {
+    /// A real number between min and max.
+    Continuous(f64, f64),
+
+    /// An integer between min and max, both inclusive.
+    Integer(i64, i64),
+
+    /// One of this many unordered options.
+    Categorical(usize),
+}
+
+/// The value of a single dimension of a position, see `Variable`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value {
+    /// Value of a `Variable::Continuous`.
+    Continuous(f64),
+
+    /// Value of a `Variable::Integer`.
+    Integer(i64),
+
+    /// Index of the option of a `Variable::Categorical`.
+    Categorical(usize),
+}
+
+/// A search space with continuous, integer and categorical variables, see
+/// `Settings::mixed`.
+///
+/// Positions are still vectors of floats. Integers are stored as whole
+/// numbers, categories as their index. After mutation and crossover,
+/// integer variables are rounded. Categorical variables have no order, so
+/// instead of the difference vectors, a categorical variable that
+/// crossover takes from the mutant gets the category of a random
+/// individual, or sometimes a random category.
+///
+/// ```
+/// use differential_evolution::{Population, SearchSpace, Settings, Value, Variable};
+///
+/// let space = SearchSpace::new(vec![Variable::Continuous(-1.0, 1.0),
+///                                   Variable::Integer(1, 10),
+///                                   Variable::Categorical(3)]);
+/// let decoder = space.clone();
+/// let cost = move |pos: &[f64]| {
+///     match decoder.decode(pos)[..] {
+///         [Value::Continuous(x), Value::Integer(n), Value::Categorical(c)] => {
+///             x * x + (n - 4).pow(2) as f64 + if c == 2 { 0.0 } else { 1.0 }
+///         }
+///         _ => unreachable!(),
+///     }
+/// };
+/// let mut de = Population::new(Settings::mixed(space, cost));
+/// de.iter().nth(5000);
+/// let (_, values) = de.best_values().unwrap();
+/// assert_eq!(Value::Integer(4), values[1]);
+/// assert_eq!(Value::Categorical(2), values[2]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchSpace {
+    variables: Vec<Variable>,
+}
+
+impl SearchSpace {
+    /// Creates the search space with one variable per dimension.
+    pub fn new(variables: Vec<Variable>) -> SearchSpace {
+        for v in &variables {
+            match *v {
+                Variable::Continuous(min, max) => assert!(min < max, "empty Continuous range"),
+                Variable::Integer(min, max) => assert!(min <= max, "empty Integer range"),
+                Variable::Categorical(n) => assert!(n > 0, "Categorical needs options"),
+            }
+        }
+        SearchSpace { variables }
+    }
+
+    /// Gets the variables.
+    pub fn variables(&self) -> &[Variable] {
+        &self.variables
+    }
+
+    /// Bounds of each dimension. Integers and categories extend by 0.5 on
+    /// both sides, so that rounding gives each value the same share.
+    pub fn bounds<T: Float>(&self) -> Vec<(T, T)> {
+        self.variables
+            .iter()
+            .map(|v| {
+                let (min, max) = match *v {
+                    Variable::Continuous(min, max) => (min, max),
+                    Variable::Integer(min, max) => (min as f64 - 0.5, max as f64 + 0.5),
+                    Variable::Categorical(n) => (-0.5, n as f64 - 0.5),
+                };
+                (T::from_f64(min), T::from_f64(max))
+            })
+            .collect()
+    }
+
+    /// Decodes a position into the typed values of the variables.
+    pub fn decode<T: Float>(&self, pos: &[T]) -> Vec<Value> {
+        self.variables
+            .iter()
+            .zip(pos.iter())
+            .map(|(v, &x)| match *v {
+                Variable::Continuous(..) => Value::Continuous(x.to_f64()),
+                Variable::Integer(..) => Value::Integer(self.nearest(v, x.to_f64()) as i64),
+                Variable::Categorical(..) => Value::Categorical(self.nearest(v, x.to_f64()) as usize),
+            })
+            .collect()
+    }
+
+    /// Rounds integer and categorical dimensions to the nearest valid
+    /// value.
+    pub fn round<T: Float>(&self, pos: &mut [T]) {
+        for (v, x) in self.variables.iter().zip(pos.iter_mut()) {
+            if let Variable::Continuous(..) = *v {
+                continue;
+            }
+            *x = T::from_f64(self.nearest(v, x.to_f64()));
+        }
+    }
+
+    // Nearest valid integer value of an integer or categorical variable.
+    fn nearest(&self, v: &Variable, x: f64) -> f64 {
+        let (min, max) = match *v {
+            Variable::Integer(min, max) => (min as f64, max as f64),
+            Variable::Categorical(n) => (0.0, (n - 1) as f64),
+            Variable::Continuous(..) => return x,
+        };
+        x.round().max(min).min(max)
+    }
+
+    /// Repairs a trial after mutation and crossover: integers are rounded,
+    /// and categorical variables that differ from the target get the
+    /// category of the donor.
+    pub fn repair<R: Rng, T: Float>(&self, rng: &mut R, target: &[T], donor: &[T], trial: &mut [T]) {
+        for (d, v) in self.variables.iter().enumerate() {
+            match *v {
+                Variable::Continuous(..) => {}
+                Variable::Integer(..) => trial[d] = T::from_f64(self.nearest(v, trial[d].to_f64())),
+                Variable::Categorical(n) => {
+                    if trial[d] == target[d] {
+                        continue;
+                    }
+                    trial[d] = if rng.next_f64() < RANDOM_CATEGORY_PROBABILITY {
+                        T::from_f64(Range::new(0, n).ind_sample(rng) as f64)
+                    } else {
+                        donor[d]
+                    };
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Snapshot of the population after a generation.
+
+use std::cmp::Ordering;
+use float::Float;
+
+/// State of the population after a generation has been evaluated and the
+/// trials have competed with their parents.
+pub struct Status<'a, C: 'a, T: 'a = f32> {
+    pub(crate) num_generations: usize,
+    pub(crate) num_cost_evaluations: usize,
+    pub(crate) num_failed_evaluations: usize,
+    pub(crate) best_cost: Option<&'a C>,
+    pub(crate) costs: Vec<&'a C>,
+    pub(crate) positions: Vec<&'a [T]>,
+    pub(crate) cr: Vec<f32>,
+    pub(crate) f: Vec<f32>,
+}
+
+/// Called after each generation with the status of the population, e.g. for
+/// logging the progress of the optimization.
+pub type Observer<C, T = f32> = Box<dyn FnMut(&Status<C, T>) + Send>;
+
+/// Summary statistics of a set of values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Statistics {
+    /// Smallest value.
+    pub min: f64,
+
+    /// Arithmetic mean.
+    pub mean: f64,
+
+    /// Median.
+    pub median: f64,
+
+    /// Largest value.
+    pub max: f64,
+
+    /// Standard deviation.
+    pub std_dev: f64,
+}
+
+impl Statistics {
+    /// Calculates the statistics of the values, ignoring NaN. All
+    /// statistics are NaN if there are no values.
+    pub fn new<I: IntoIterator<Item = f64>>(values: I) -> Statistics {
+        let mut values: Vec<f64> = values.into_iter().filter(|x| !x.is_nan()).collect();
+        if values.is_empty() {
+            return Statistics {
+                min: f64::NAN,
+                mean: f64::NAN,
+                median: f64::NAN,
+                max: f64::NAN,
+                std_dev: f64::NAN,
+            };
+        }
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        let n = values.len();
+        let mean = values.iter().sum::<f64>() / n as f64;
+        let variance = values.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n as f64;
+        let median = if n % 2 == 1 {
+            values[n / 2]
+        } else {
+            (values[n / 2 - 1] + values[n / 2]) / 2.0
+        };
+        Statistics {
+            min: values[0],
+            mean,
+            median,
+            max: values[n - 1],
+            std_dev: variance.sqrt(),
+        }
+    }
+}
+
+impl<'a, C, T: Float> Status<'a, C, T> {
+    /// Number of generations that have been evaluated so far.
+    pub fn num_generations(&self) -> usize {
+        self.num_generations
+    }
+
+    /// Total number of cost evaluations so far. Failed evaluations are not
+    /// included.
+    pub fn num_cost_evaluations(&self) -> usize {
+        self.num_cost_evaluations
+    }
+
+    /// Number of failed evaluations so far, including the retried ones.
+    pub fn num_failed_evaluations(&self) -> usize {
+        self.num_failed_evaluations
+    }
+
+    /// Cost of the best solution found so far.
+    pub fn best_cost(&self) -> Option<&'a C> {
+        self.best_cost
+    }
+
+    /// Costs of all individuals of the population.
+    pub fn costs(&self) -> &[&'a C] {
+        &self.costs
+    }
+
+    /// Positions of all individuals of the population.
+    pub fn positions(&self) -> &[&'a [T]] {
+        &self.positions
+    }
+
+    /// Crossover control parameters `cr` of all individuals.
+    pub fn cr(&self) -> &[f32] {
+        &self.cr
+    }
+
+    /// Amplification factors `f` of all individuals.
+    pub fn f(&self) -> &[f32] {
+        &self.f
+    }
+
+    /// Best, mean, median and worst cost of the population, as `min`,
+    /// `mean`, `median` and `max`. Failed evaluations are ignored.
+    pub fn cost_statistics(&self) -> Statistics
+        where C: Clone + Into<f64>
+    {
+        Statistics::new(self.costs.iter().map(|&c| c.clone().into()))
+    }
+
+    /// Distribution of the `cr` values of the population.
+    pub fn cr_statistics(&self) -> Statistics {
+        Statistics::new(self.cr.iter().map(|&cr| cr as f64))
+    }
+
+    /// Distribution of the `f` values of the population.
+    pub fn f_statistics(&self) -> Statistics {
+        Statistics::new(self.f.iter().map(|&f| f as f64))
+    }
+
+    /// Mean Euclidean distance of the positions to their centroid. When this
+    /// approaches zero, the population has converged, possibly prematurely.
+    pub fn diversity(&self) -> f64 {
+        let n = self.positions.len() as f64;
+        let dim = self.positions[0].len();
+        let centroid: Vec<f64> = (0..dim)
+            .map(|d| self.positions.iter().map(|pos| pos[d].to_f64()).sum::<f64>() / n)
+            .collect();
+        let total: f64 = self.positions
+            .iter()
+            .map(|pos| {
+                pos.iter()
+                    .zip(centroid.iter())
+                    .map(|(x, c)| (x.to_f64() - c) * (x.to_f64() - c))
+                    .sum::<f64>()
+                    .sqrt()
+            })
+            .sum();
+        total / n
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Termination criteria for `Population::run_until`.
+//!
+//! Criteria are checked after each generation, and can be combined with
+//! `Any` and `All`:
+//!
+//! ```
+//! use differential_evolution::self_adaptive_de;
+//! use differential_evolution::termination::{Any, MaxEvaluations, Reason, Stagnation, TargetCost};
+//!
+//! let mut de = self_adaptive_de(vec![(-10.0, 10.0); 3], |pos: &[f32]| {
+//!     pos.iter().fold(0.0, |sum, x| sum + x * x)
+//! });
+//! let reason = de.run_until(Any(vec![Box::new(MaxEvaluations(100000)),
+//!                                    Box::new(TargetCost(1e-6)),
+//!                                    Box::new(Stagnation::new(50))]));
+//! assert!(reason != Reason::MaxEvaluations);
+//! ```
+
+use std::time::{Duration, Instant};
+use float::Float;
+use status::Status;
+
+/// Why the optimization has stopped.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Reason {
+    /// `MaxEvaluations` was reached.
+    MaxEvaluations,
+
+    /// `MaxGenerations` was reached.
+    MaxGenerations,
+
+    /// `TargetCost` was reached.
+    TargetCost,
+
+    /// The best cost did not improve, see `Stagnation`.
+    Stagnation,
+
+    /// `TimeLimit` was reached.
+    TimeLimit,
+
+    /// The population has converged, see `CostSpread` and `PositionSpread`.
+    Converged,
+
+    /// All criteria of `All` have fired, with their reasons.
+    All(Vec<Reason>),
+
+    /// A user defined criterion has fired.
+    Custom(String),
+}
+
+/// Decides when the optimization should stop.
+pub trait Termination<C, T = f32> {
+    /// Called after each generation. Returns why the optimization should
+    /// stop, or `None` to continue.
+    fn check(&mut self, status: &Status<C, T>) -> Option<Reason>;
+}
+
+impl<C, T, X: Termination<C, T> + ?Sized> Termination<C, T> for Box<X> {
+    fn check(&mut self, status: &Status<C, T>) -> Option<Reason> {
+        (**self).check(status)
+    }
+}
+
+/// Stops when any of the criteria fires, and returns the reason of the
+/// first one. All criteria are checked every generation, so that stateful
+/// criteria stay up to date.
+pub struct Any<C, T = f32>(pub Vec<Box<dyn Termination<C, T>>>);
+
+impl<C, T> Termination<C, T> for Any<C, T> {
+    fn check(&mut self, status: &Status<C, T>) -> Option<Reason> {
+        self.0.iter_mut().fold(None, |reason, criterion| {
+            let r = criterion.check(status);
+            reason.or(r)
+        })
+    }
+}
+
+/// Stops when all criteria fire in the same generation.
+pub struct All<C, T = f32>(pub Vec<Box<dyn Termination<C, T>>>);
+
+impl<C, T> Termination<C, T> for All<C, T> {
+    fn check(&mut self, status: &Status<C, T>) -> Option<Reason> {
+        let reasons: Vec<Option<Reason>> = self.0.iter_mut().map(|c| c.check(status)).collect();
+        if reasons.iter().all(|r| r.is_some()) {
+            Some(Reason::All(reasons.into_iter().map(|r| r.unwrap()).collect()))
+        } else {
+            None
+        }
+    }
+}
+
+/// Stops when at least this many cost evaluations have been performed,
+/// counting failed evaluations too. Since criteria are checked after each
+/// generation, it can overshoot by less than the population size.
+pub struct MaxEvaluations(pub usize);
+
+impl<C, T: Float> Termination<C, T> for MaxEvaluations {
+    fn check(&mut self, status: &Status<C, T>) -> Option<Reason> {
+        if status.num_cost_evaluations() + status.num_failed_evaluations() >= self.0 {
+            Some(Reason::MaxEvaluations)
+        } else {
+            None
+        }
+    }
+}
+
+/// Stops after this many generations.
+pub struct MaxGenerations(pub usize);
+
+impl<C, T: Float> Termination<C, T> for MaxGenerations {
+    fn check(&mut self, status: &Status<C, T>) -> Option<Reason> {
+        if status.num_generations() >= self.0 {
+            Some(Reason::MaxGenerations)
+        } else {
+            None
+        }
+    }
+}
+
+/// Stops when the best cost is less than or equal to the target.
+pub struct TargetCost<C>(pub C);
+
+impl<C: PartialOrd, T: Float> Termination<C, T> for TargetCost<C> {
+    fn check(&mut self, status: &Status<C, T>) -> Option<Reason> {
+        match status.best_cost() {
+            Some(cost) if *cost <= self.0 => Some(Reason::TargetCost),
+            _ => None,
+        }
+    }
+}
+
+/// Stops when the best cost has not improved for a number of generations.
+pub struct Stagnation<C> {
+    generations: usize,
+    best_cost: Option<C>,
+    num_stagnating: usize,
+}
+
+impl<C> Stagnation<C> {
+    /// Stops after `generations` generations without improvement.
+    pub fn new(generations: usize) -> Stagnation<C> {
+        Stagnation {
+            generations,
+            best_cost: None,
+            num_stagnating: 0,
+        }
+    }
+}
+
+impl<C: PartialOrd + Clone, T: Float> Termination<C, T> for Stagnation<C> {
+    fn check(&mut self, status: &Status<C, T>) -> Option<Reason> {
+        let cost = status.best_cost()?;
+        let is_improved = match self.best_cost {
+            Some(ref best) => cost < best,
+            None => true,
+        };
+        if is_improved {
+            self.best_cost = Some(cost.clone());
+            self.num_stagnating = 0;
+            return None;
+        }
+        self.num_stagnating += 1;
+        if self.num_stagnating >= self.generations {
+            Some(Reason::Stagnation)
+        } else {
+            None
+        }
+    }
+}
+
+/// Stops when the wall-clock time since its creation exceeds a limit.
+pub struct TimeLimit {
+    start: Instant,
+    limit: Duration,
+}
+
+impl TimeLimit {
+    /// Starts measuring the time now.
+    pub fn new(limit: Duration) -> TimeLimit {
+        TimeLimit {
+            start: Instant::now(),
+            limit,
+        }
+    }
+}
+
+impl<C, T: Float> Termination<C, T> for TimeLimit {
+    fn check(&mut self, _status: &Status<C, T>) -> Option<Reason> {
+        if self.start.elapsed() >= self.limit {
+            Some(Reason::TimeLimit)
+        } else {
+            None
+        }
+    }
+}
+
+/// Stops when the standard deviation of the population's costs is at most
+/// `atol + tol * |mean|`, like scipy's `differential_evolution`.
+pub struct CostSpread {
+    /// Relative tolerance.
+    pub tol: f64,
+
+    /// Absolute tolerance.
+    pub atol: f64,
+}
+
+impl<C: Clone + Into<f64>, T: Float> Termination<C, T> for CostSpread {
+    fn check(&mut self, status: &Status<C, T>) -> Option<Reason> {
+        let costs: Vec<f64> = status.costs().iter().map(|&c| c.clone().into()).collect();
+        let n = costs.len() as f64;
+        let mean = costs.iter().sum::<f64>() / n;
+        let variance = costs.iter().map(|c| (c - mean) * (c - mean)).sum::<f64>() / n;
+        if variance.sqrt() <= self.atol + self.tol * mean.abs() {
+            Some(Reason::Converged)
+        } else {
+            None
+        }
+    }
+}
+
+/// Stops when in each dimension the positions of all individuals lie
+/// within this distance of each other.
+pub struct PositionSpread(pub f64);
+
+impl<C, T: Float> Termination<C, T> for PositionSpread {
+    fn check(&mut self, status: &Status<C, T>) -> Option<Reason> {
+        let positions = status.positions();
+        let is_converged = (0..positions[0].len()).all(|d| {
+            let (min, max) = positions.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), pos| {
+                let x = pos[d].to_f64();
+                (min.min(x), max.max(x))
+            });
+            max - min <= self.0
+        });
+        if is_converged {
+            Some(Reason::Converged)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright 2016 Martin Ankerl.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Read-only access to the individuals of a population.
+
+use rand::Rng;
+use super::{Constraint, Float, Individual, Population, Settings};
+
+/// Read-only view of an individual of the population, e.g. for plotting
+/// the population or for custom convergence heuristics.
+pub struct IndividualView<'a, C, T>
+    where C: 'a + PartialOrd + Clone,
+          T: 'a
+{
+    ind: &'a Individual<C, T>,
+}
+
+impl<'a, C, T> IndividualView<'a, C, T>
+    where C: PartialOrd + Clone,
+          T: Float
+{
+    /// Position of the individual.
+    pub fn pos(&self) -> &'a [T] {
+        &self.ind.pos
+    }
+
+    /// Cost of the individual, `None` if it has not been evaluated yet.
+    pub fn cost(&self) -> Option<&'a C> {
+        self.ind.cost.as_ref()
+    }
+
+    /// The crossover control parameter `cr` of the individual.
+    pub fn cr(&self) -> f32 {
+        self.ind.cr
+    }
+
+    /// The amplification factor `f` of the individual.
+    pub fn f(&self) -> f32 {
+        self.ind.f
+    }
+
+    /// Values of the constraints, empty if unconstrained or not evaluated.
+    pub fn constraints(&self) -> &'a [Constraint<T>] {
+        &self.ind.constraints
+    }
+}
+
+impl<F, R, C, T> Population<F, R, C, T>
+    where F: Fn(&[T]) -> C,
+          R: Rng,
+          C: PartialOrd + Clone,
+          T: Float
+{
+    /// Gets the individuals of the population. For each index this is the
+    /// better of the parent and its evaluated trial, so it is the
+    /// population that the next generation will be created from.
+    pub fn individuals(&self) -> Vec<IndividualView<'_, C, T>> {
+        self.survivors().into_iter().map(|ind| IndividualView { ind }).collect()
+    }
+
+    /// Gets the trials of the current generation, in the same order as
+    /// `individuals()`. Their costs are `None` until they are evaluated.
+    pub fn trials(&self) -> Vec<IndividualView<'_, C, T>> {
+        self.curr.iter().map(|ind| IndividualView { ind }).collect()
+    }
+
+    /// Index of the current generation, starting at 0.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Number of individuals, which changes with population size reduction.
+    pub fn pop_size(&self) -> usize {
+        self.curr.len()
+    }
+
+    /// Number of dimensions of the positions.
+    pub fn dim(&self) -> usize {
+        self.settings.min_max_pos.len()
+    }
+
+    /// Gets the settings of the population.
+    pub fn settings(&self) -> &Settings<F, R, C, T> {
+        &self.settings
+    }
+}